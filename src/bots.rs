@@ -0,0 +1,225 @@
+use std::collections::HashSet;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::draft_database::{DraftItem, DraftSet};
+use crate::draft_engine::{DraftItemId, PickPreference, PickQueue};
+
+// How much the heuristic values a new type, a type its team already has, and a role its team doesn't fill yet,
+// in base stat points
+const NEW_TYPE_BONUS: i32 = 40;
+const SHARED_TYPE_PENALTY: i32 = 30;
+const NEW_ROLE_BONUS: i32 = 50;
+
+/// How a bot chooses what to pick
+pub trait PickStrategy {
+    /// Chooses one of the items in `pack`, which is never empty. `drafted` are the items the bot has already picked.
+    fn choose(&self, pack: &[DraftItemId], drafted: &[DraftItemId], draft_set: &DraftSet, rng: &mut StdRng) -> DraftItemId;
+}
+
+/// Picks any item in the pack
+pub struct RandomPick;
+
+impl PickStrategy for RandomPick {
+    fn choose(&self, pack: &[DraftItemId], _drafted: &[DraftItemId], _draft_set: &DraftSet, rng: &mut StdRng) -> DraftItemId {
+        *pack.choose(rng).unwrap()
+    }
+}
+
+/// Picks the first item in the pack
+pub struct FirstItemPick;
+
+impl PickStrategy for FirstItemPick {
+    fn choose(&self, pack: &[DraftItemId], _drafted: &[DraftItemId], _draft_set: &DraftSet, _rng: &mut StdRng) -> DraftItemId {
+        pack[0]
+    }
+}
+
+/// Picks the item with the highest base stat total, favouring items that add types and roles the team doesn't have
+/// yet, and avoiding types it already has. Also picks for players who run out of time.
+pub struct HeuristicPick;
+
+impl PickStrategy for HeuristicPick {
+    fn choose(&self, pack: &[DraftItemId], drafted: &[DraftItemId], draft_set: &DraftSet, _rng: &mut StdRng) -> DraftItemId {
+        let team: Vec<&DraftItem> = drafted.iter().filter_map(|id| draft_set.get_item_by_id(id)).collect();
+        let team_types: HashSet<&String> = team.iter().flat_map(|item| item.get_types()).collect();
+        let team_roles: HashSet<Role> = team.iter().filter_map(|item| Role::of(item)).collect();
+        let score = |id: &DraftItemId| {
+            let item = match draft_set.get_item_by_id(id) {
+                Some(item) => item,
+                None => return i32::MIN,
+            };
+            let (shared_types, new_types): (Vec<&String>, Vec<&String>) = item.get_types().iter()
+                .partition(|t| team_types.contains(t));
+            let role_bonus = match Role::of(item) {
+                Some(role) if !team_roles.contains(&role) => NEW_ROLE_BONUS,
+                _ => 0,
+            };
+            item.get_base_stats().map_or(0, |stats| stats.total() as i32)
+                + NEW_TYPE_BONUS * new_types.len() as i32
+                - SHARED_TYPE_PENALTY * shared_types.len() as i32
+                + role_bonus
+        };
+        // Ties go to the earlier item
+        let mut best = pack[0];
+        for id in &pack[1..] {
+            if score(id) > score(&best) {
+                best = *id;
+            }
+        }
+        best
+    }
+}
+
+/// What a Pokemon does on a team, judged from its base stats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Role {
+    PhysicalAttacker,
+    SpecialAttacker,
+    PhysicalWall,
+    SpecialWall,
+}
+
+impl Role {
+    fn of(item: &DraftItem) -> Option<Role> {
+        let stats = item.get_base_stats()?;
+        // Attackers are judged on their offense and walls on their bulk, so that the two are comparable
+        [
+            (Role::PhysicalAttacker, stats.atk),
+            (Role::SpecialAttacker, stats.spa),
+            (Role::PhysicalWall, (stats.hp + stats.def) / 2),
+            (Role::SpecialWall, (stats.hp + stats.spd) / 2),
+        ].into_iter()
+            .max_by_key(|&(_, stat)| stat)
+            .map(|(role, _)| role)
+    }
+}
+
+/// The strategies bots can be added with
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BotStrategy {
+    Random,
+    FirstItem,
+    Heuristic,
+}
+
+impl BotStrategy {
+    pub fn pick_strategy(&self) -> &'static dyn PickStrategy {
+        match self {
+            BotStrategy::Random => &RandomPick,
+            BotStrategy::FirstItem => &FirstItemPick,
+            BotStrategy::Heuristic => &HeuristicPick,
+        }
+    }
+
+    /// The name bots with this strategy are given, along with a number
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            BotStrategy::Random => "Random Bot",
+            BotStrategy::FirstItem => "First Pick Bot",
+            BotStrategy::Heuristic => "Smart Bot",
+        }
+    }
+}
+
+/// The best item in the pack according to a player's pick queue, if any of their queued items are in it
+pub fn find_queued_item(pick_queue: &PickQueue, pack: &[DraftItemId], draft_set: &DraftSet) -> Option<DraftItemId> {
+    pick_queue.preferences.iter()
+        .find_map(|preference| pack.iter().copied().find(|id| match preference {
+            PickPreference::Item(item_id) => id == item_id,
+            PickPreference::Species(species) => draft_set.get_item_by_id(id)
+                .is_some_and(|item| item.get_species().eq_ignore_ascii_case(species)),
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::SeedableRng;
+
+    use crate::test_fixtures::{load_set, sets_of};
+
+    /// Garchomp, Blissey, Ferrothorn, Alakazam, Rotom-Wash and Vaporeon, as items 0 to 5
+    fn bot_set() -> DraftSet {
+        load_set("bots", &sets_of(&["Garchomp", "Blissey", "Ferrothorn", "Alakazam", "Rotom-Wash", "Vaporeon"]), None)
+    }
+
+    fn queue(preferences: Vec<PickPreference>) -> PickQueue {
+        PickQueue { preferences, pick_immediately: false }
+    }
+
+    #[test]
+    fn first_item_pick_takes_the_front_of_the_pack() {
+        let draft_set = bot_set();
+        assert_eq!(FirstItemPick.choose(&[4, 2, 0], &[1], &draft_set, &mut StdRng::seed_from_u64(1)), 4);
+    }
+
+    #[test]
+    fn random_pick_takes_a_seeded_item_from_the_pack() {
+        let draft_set = bot_set();
+        let pack = [5, 3, 1, 0];
+        let picks: HashSet<DraftItemId> = (0..20)
+            .map(|seed| RandomPick.choose(&pack, &[], &draft_set, &mut StdRng::seed_from_u64(seed)))
+            .collect();
+        assert!(picks.iter().all(|pick| pack.contains(pick)));
+        assert!(picks.len() > 1);
+        let pick = |seed| RandomPick.choose(&pack, &[], &draft_set, &mut StdRng::seed_from_u64(seed));
+        assert_eq!(pick(7), pick(7));
+    }
+
+    #[test]
+    fn bot_strategies_are_named_in_snake_case() {
+        assert_eq!(serde_json::to_string(&BotStrategy::FirstItem).unwrap(), "\"first_item\"");
+        assert_eq!(serde_json::from_str::<BotStrategy>("\"heuristic\"").unwrap(), BotStrategy::Heuristic);
+        let draft_set = bot_set();
+        assert_eq!(BotStrategy::FirstItem.pick_strategy().choose(&[3, 1], &[], &draft_set, &mut StdRng::seed_from_u64(1)), 3);
+    }
+
+    #[test]
+    fn finds_the_first_queued_item_in_the_pack() {
+        let draft_set = bot_set();
+        let pick_queue = queue(vec![PickPreference::Species("vaporeon".to_string()), PickPreference::Item(1)]);
+        // Earlier preferences win, and species match whatever the case
+        assert_eq!(find_queued_item(&pick_queue, &[1, 5], &draft_set), Some(5));
+        assert_eq!(find_queued_item(&pick_queue, &[2, 1], &draft_set), Some(1));
+        assert_eq!(find_queued_item(&pick_queue, &[2, 3], &draft_set), None);
+        assert_eq!(find_queued_item(&queue(vec![]), &[2, 3], &draft_set), None);
+    }
+
+    fn heuristic_pick(pack: &[DraftItemId], drafted: &[DraftItemId]) -> DraftItemId {
+        HeuristicPick.choose(pack, drafted, &bot_set(), &mut StdRng::seed_from_u64(1))
+    }
+
+    #[test]
+    fn heuristic_picks_the_highest_base_stat_total_for_an_empty_team() {
+        assert_eq!(heuristic_pick(&[3, 1, 0, 5], &[]), 0);
+    }
+
+    #[test]
+    fn heuristic_avoids_types_the_team_has() {
+        // Rotom-Wash has the higher total, but shares Water with Vaporeon
+        assert_eq!(heuristic_pick(&[4, 3], &[]), 4);
+        assert_eq!(heuristic_pick(&[4, 3], &[5]), 3);
+    }
+
+    #[test]
+    fn heuristic_fills_roles_the_team_lacks() {
+        // Rotom-Wash would be a second special attacker next to Alakazam, so the special wall is picked instead
+        assert_eq!(heuristic_pick(&[4, 5], &[]), 4);
+        assert_eq!(heuristic_pick(&[4, 5], &[3]), 5);
+    }
+
+    #[test]
+    fn judges_roles_from_base_stats() {
+        let draft_set = bot_set();
+        let role = |id: DraftItemId| Role::of(draft_set.get_item_by_id(&id).unwrap());
+        assert_eq!(role(0), Some(Role::PhysicalAttacker));
+        assert_eq!(role(1), Some(Role::SpecialWall));
+        assert_eq!(role(2), Some(Role::PhysicalWall));
+        assert_eq!(role(3), Some(Role::SpecialAttacker));
+    }
+}
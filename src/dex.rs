@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde_derive::Deserialize;
+
+use crate::pokemon_set::StatSpread;
+
+pub const DEX_FOLDER: &str = "dex";
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SpeciesData {
+    /// Asset name (without extension) under `static/assets`, e.g. "479-2"
+    pub sprite: String,
+    pub types: Vec<String>,
+    pub abilities: Vec<String>,
+    pub base_stats: StatSpread,
+    /// The species this is a forme of, e.g. "Rotom" for "Rotom-Wash". Species without one are their own base species.
+    #[serde(default)]
+    pub base_species: Option<String>,
+    /// The first stage of the species' evolutionary family, e.g. "Eevee". Species without one are their own family.
+    #[serde(default)]
+    pub family: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ItemData {
+    pub sprite: String,
+    /// Items tied to a type (e.g. Damp Rock) are coloured like that type
+    #[serde(rename = "type")]
+    pub item_type: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct MoveData {
+    #[serde(rename = "type")]
+    pub move_type: String,
+}
+
+/// Local Pokemon data used to render and check draft sets. Lives in `data/dex`.
+pub struct Dex {
+    species: HashMap<String, SpeciesData>,
+    items: HashMap<String, ItemData>,
+    moves: HashMap<String, MoveData>,
+}
+
+impl Dex {
+    pub fn from_folder(dir: &Path) -> io::Result<Dex> {
+        Ok(Dex {
+            species: read_json_table(&dir.join("species.json"))?,
+            items: read_json_table(&dir.join("items.json"))?,
+            moves: read_json_table(&dir.join("moves.json"))?,
+        })
+    }
+
+    pub fn get_species(&self, name: &str) -> Option<&SpeciesData> {
+        self.species.get(name)
+    }
+
+    pub fn get_item(&self, name: &str) -> Option<&ItemData> {
+        self.items.get(name)
+    }
+
+    pub fn get_move(&self, name: &str) -> Option<&MoveData> {
+        self.moves.get(name)
+    }
+}
+
+fn read_json_table<T: DeserializeOwned>(path: &Path) -> io::Result<HashMap<String, T>> {
+    let contents = fs::read_to_string(path)?;
+    serde_json::from_str(&contents)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {e}", path.display())))
+}
@@ -1,15 +1,98 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::fs;
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use rand::Rng;
 use rand::seq::SliceRandom;
+use serde_derive::{Deserialize, Serialize};
 
-use crate::draft_engine::DraftItemId;
+use crate::dex::{DEX_FOLDER, Dex};
+use crate::draft_engine::{CollationRule, DraftError, DraftItemId, DraftResult, LobbyConfig, UniquenessKey};
+use crate::pokemon_set::{PokemonSet, SetParseError, StatSpread};
+use crate::set_renderer;
+
+pub const DRAFT_SETS_FILE: &str = "draft_sets.txt";
+pub const SET_MANIFEST_FILE: &str = "set.json";
+
+/// Describes a draft set on the homepage. Read from the optional `set.json` in the set's folder.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetManifest {
+    pub display_name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub recommended_pack_size: Option<usize>,
+    #[serde(default)]
+    pub recommended_pick_time_s: Option<u64>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// What counts as a duplicate item, unless a lobby chooses otherwise
+    #[serde(default)]
+    pub uniqueness: UniquenessKey,
+    /// The tier of each species, for sets that don't give one with a `Tier:` line in `draft_sets.txt`
+    #[serde(default)]
+    pub tiers: BTreeMap<String, String>,
+    /// How many items of each tier go in every pack. Packs are shuffled from the whole set if this is empty.
+    #[serde(default)]
+    pub pack_collation: Vec<TierCount>,
+}
+
+/// A pack collation rule: every pack gets `count` items of `tier`
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TierCount {
+    pub tier: String,
+    pub count: usize,
+}
+
+impl SetManifest {
+    /// The config for lobbies that don't choose their own, following the set's recommendations
+    pub fn default_lobby_config(&self) -> LobbyConfig {
+        let mut config = LobbyConfig::default();
+        config.pack_size = self.recommended_pack_size.or(config.pack_size);
+        config.uniqueness = self.uniqueness;
+        if let Some(pick_time_s) = self.recommended_pick_time_s {
+            config.time_per_pack_item_s = pick_time_s as f64;
+        }
+        config
+    }
+
+    fn from_folder(set_name: &str, set_dir: &Path) -> Result<SetManifest, DraftDbError> {
+        let manifest_file = set_dir.join(SET_MANIFEST_FILE);
+        if !manifest_file.is_file() {
+            return Ok(SetManifest {
+                display_name: set_name.to_string(),
+                description: String::new(),
+                author: None,
+                recommended_pack_size: None,
+                recommended_pick_time_s: None,
+                tags: vec![],
+                uniqueness: UniquenessKey::Species,
+                tiers: BTreeMap::new(),
+                pack_collation: vec![],
+            });
+        }
+        let contents = fs::read_to_string(&manifest_file)
+            .map_err(|e| DraftDbError::new(set_name, &manifest_file, DraftDbErrorKind::Io(e)))?;
+        serde_json::from_str(&contents)
+            .map_err(|e| DraftDbError::new(set_name, &manifest_file, DraftDbErrorKind::InvalidManifest(e)))
+    }
+}
 
 pub struct DraftItem {
+    species: String,
+    base_species: String,
+    family: String,
+    types: Vec<String>,
+    base_stats: Option<StatSpread>,
     raw_html: String,
     simple_text: String,
     stats_html: String,
+    tier: Option<String>,
+    set: PokemonSet,
 }
 
 impl DraftItem {
@@ -22,81 +105,615 @@ impl DraftItem {
     pub fn get_stats(&self) -> &String {
         return &self.stats_html;
     }
+    pub fn get_species(&self) -> &String {
+        &self.species
+    }
+    pub fn get_types(&self) -> &Vec<String> {
+        &self.types
+    }
+    /// The species' base stats, if it's in the dex
+    pub fn get_base_stats(&self) -> Option<&StatSpread> {
+        self.base_stats.as_ref()
+    }
+    pub fn get_tier(&self) -> Option<&String> {
+        self.tier.as_ref()
+    }
+
+    /// Items with the same key are duplicates of each other, so only one of them is drafted
+    pub fn get_uniqueness_key(&self, uniqueness: UniquenessKey) -> &String {
+        match uniqueness {
+            UniquenessKey::Species => &self.species,
+            UniquenessKey::BaseSpecies => &self.base_species,
+            UniquenessKey::Family => &self.family,
+        }
+    }
+
+    /// Whether a ban list names this item's species, base species or family
+    pub fn is_banned(&self, banned: &[String]) -> bool {
+        banned.iter().any(|name| self.has_name(name))
+    }
+
+    fn has_name(&self, name: &str) -> bool {
+        [&self.species, &self.base_species, &self.family].iter().any(|key| key.eq_ignore_ascii_case(name))
+    }
+}
+
+/// A loaded set as listed on the homepage and by `/api/sets`
+#[derive(Serialize, Debug, Clone)]
+pub struct SetSummary {
+    pub name: String,
+    pub manifest: SetManifest,
+    pub item_count: usize,
+    pub species_count: usize,
+}
+
+/// One item of a set, as returned by `/api/sets/{name}`
+#[derive(Serialize, Debug, Clone)]
+pub struct SetItemSummary {
+    pub id: DraftItemId,
+    pub species: String,
+    pub raw: String,
+}
+
+/// Everything about an item, as returned by `/api/draft/{lobby}/{player}`
+#[derive(Serialize, Debug, Clone)]
+pub struct DraftItemDetails {
+    pub id: DraftItemId,
+    pub species: String,
+    pub raw: String,
+    pub set: PokemonSet,
+    pub types: Vec<String>,
+    pub base_stats: Option<StatSpread>,
+    pub tier: Option<String>,
 }
 
 pub struct DraftSet {
-    deduplicated_ids: HashMap<String, Vec<DraftItemId>>,
+    manifest: SetManifest,
+    // Ordered by species, so that the same random number generator always picks the same items
+    deduplicated_ids: BTreeMap<String, Vec<DraftItemId>>,
     id_to_item: HashMap<DraftItemId, DraftItem>,
 }
 
+#[derive(Debug)]
+pub enum DraftDbErrorKind {
+    Io(io::Error),
+    UnparsableName,
+    InvalidSets(SetParseError),
+    InvalidManifest(serde_json::Error),
+    EmptySet,
+    MissingStatsPartner(DraftItemId),
+    UnknownItemId(DraftItemId),
+    TooFewSpecies { unique_species: usize, required: usize },
+    UnfillablePackCollation(DraftError),
+}
+
+/// A problem with a draft set folder, naming the set and the file it was found in
+#[derive(Debug)]
+pub struct DraftDbError {
+    pub set_name: Option<String>,
+    pub path: PathBuf,
+    pub kind: DraftDbErrorKind,
+}
+
+impl DraftDbError {
+    fn new(set_name: &str, path: &Path, kind: DraftDbErrorKind) -> DraftDbError {
+        DraftDbError { set_name: Some(set_name.to_string()), path: path.to_path_buf(), kind }
+    }
+
+    /// Sets with too few species, or too few of a tier, can still be used by smaller lobbies, so they're loaded anyway
+    pub fn set_is_usable(&self) -> bool {
+        matches!(self.kind, DraftDbErrorKind::TooFewSpecies { .. } | DraftDbErrorKind::UnfillablePackCollation(_))
+    }
+}
+
+impl fmt::Display for DraftDbErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DraftDbErrorKind::Io(e) => write!(f, "{e}"),
+            DraftDbErrorKind::UnparsableName => write!(f, "couldn't parse file name"),
+            DraftDbErrorKind::InvalidSets(e) => write!(f, "{e}"),
+            DraftDbErrorKind::InvalidManifest(e) => write!(f, "{e}"),
+            DraftDbErrorKind::EmptySet => write!(f, "contains no sets"),
+            DraftDbErrorKind::MissingStatsPartner(id) => write!(f, "cached template {id}.html has no generated_stats partner"),
+            DraftDbErrorKind::UnknownItemId(id) => write!(f, "cached file {id}.html doesn't match any set"),
+            DraftDbErrorKind::TooFewSpecies { unique_species, required } =>
+                write!(f, "has {unique_species} unique species, but a full lobby with the set's default config needs {required}"),
+            DraftDbErrorKind::UnfillablePackCollation(e) => write!(f, "pack collation doesn't fit the set's default config: {e}"),
+        }
+    }
+}
+
+impl fmt::Display for DraftDbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.set_name {
+            Some(set_name) => write!(f, "[{set_name}] {}: {}", self.path.display(), self.kind),
+            None => write!(f, "{}: {}", self.path.display(), self.kind),
+        }
+    }
+}
+
+impl std::error::Error for DraftDbError {}
+
 impl DraftSet {
-    pub fn from_folder(dir_name: &str) -> io::Result<DraftSet> {
-        let dir: String = dir_name.to_string() + &*"/generated".to_string();
-        let template_path = Path::new(&dir);
-        let mut items: HashMap<DraftItemId, DraftItem> = HashMap::new();
-        let mut deduplicated_ids: HashMap<String, Vec<DraftItemId>> = HashMap::new();
-        let mut i = 0;
-        for entry in fs::read_dir(template_path)? {
-            let entry_path = entry?.path();
-            let raw_html = fs::read_to_string(&entry_path)?;
-            let simple_text = html2text::from_read(fs::File::open(&entry_path)?, 999).trim_start().to_string();
-
-            let tmp: Vec<&str> = simple_text.split("@").collect();
-            let pkmn_name = tmp[0].trim().to_string();
-            if deduplicated_ids.contains_key(&pkmn_name) {
-                deduplicated_ids.get_mut(&pkmn_name).unwrap().push(i);
-            } else {
-                deduplicated_ids.insert(pkmn_name, vec!(i));
+    /// Loads a set from `draft_sets.txt` in the given folder. The `generated` and `generated_stats`
+    /// folders are an optional cache of pre-rendered HTML, keyed by the set's index in that file.
+    /// Sets without cached HTML are rendered from the parsed set.
+    pub fn from_folder(set_name: &str, set_dir: &Path, dex: &Dex) -> Result<DraftSet, DraftDbError> {
+        let manifest = SetManifest::from_folder(set_name, set_dir)?;
+        let sets_file = set_dir.join(DRAFT_SETS_FILE);
+        let sets_text = fs::read_to_string(&sets_file)
+            .map_err(|e| DraftDbError::new(set_name, &sets_file, DraftDbErrorKind::Io(e)))?;
+        let pokemon_sets = PokemonSet::parse_all(&sets_text)
+            .map_err(|e| DraftDbError::new(set_name, &sets_file, DraftDbErrorKind::InvalidSets(e)))?;
+        if pokemon_sets.is_empty() {
+            return Err(DraftDbError::new(set_name, &sets_file, DraftDbErrorKind::EmptySet));
+        }
+
+        let template_dir = set_dir.join("generated");
+        let stats_dir = set_dir.join("generated_stats");
+        let mut cached_templates = read_html_cache(set_name, &template_dir)?;
+        let mut cached_stats = read_html_cache(set_name, &stats_dir)?;
+        for (cache_dir, cache) in [(&template_dir, &cached_templates), (&stats_dir, &cached_stats)] {
+            if let Some(&id) = cache.keys().find(|&&id| id as usize >= pokemon_sets.len()) {
+                return Err(DraftDbError::new(set_name, &cache_dir.join(format!("{id}.html")), DraftDbErrorKind::UnknownItemId(id)));
             }
+        }
+        if let Some(&id) = cached_templates.keys().find(|id| !cached_stats.contains_key(id)) {
+            return Err(DraftDbError::new(set_name, &template_dir.join(format!("{id}.html")), DraftDbErrorKind::MissingStatsPartner(id)));
+        }
 
-            let stats_file = dir_name.to_string() + &*"/generated_stats/".to_string() + &entry_path.file_name().unwrap().to_str().unwrap().to_string();
-            let stats_html = fs::read_to_string(stats_file)?;
-            items.insert(i, DraftItem{ raw_html , simple_text, stats_html});
-            i += 1;
-        };
+        let mut items: HashMap<DraftItemId, DraftItem> = HashMap::new();
+        let mut deduplicated_ids: BTreeMap<String, Vec<DraftItemId>> = BTreeMap::new();
+        for (i, set) in pokemon_sets.into_iter().enumerate() {
+            let i = i as DraftItemId;
+            deduplicated_ids.entry(set.species.clone()).or_default().push(i);
+
+            let simple_text = set.to_string();
+            let raw_html = cached_templates.remove(&i)
+                .unwrap_or_else(|| set_renderer::render_pokepaste(&set, dex));
+            let stats_html = cached_stats.remove(&i)
+                .unwrap_or_else(|| set_renderer::render_stat_bars(&set, dex));
+            let tier = set.tier.clone().or_else(|| manifest.tiers.get(&set.species).cloned());
+            let species_data = dex.get_species(&set.species);
+            let base_species = species_data.and_then(|data| data.base_species.clone())
+                .unwrap_or_else(|| set.species.clone());
+            let family = species_data.and_then(|data| data.family.clone())
+                .unwrap_or_else(|| base_species.clone());
+            let types = species_data.map(|data| data.types.clone()).unwrap_or_default();
+            let base_stats = species_data.map(|data| data.base_stats);
+            items.insert(i, DraftItem {
+                species: set.species.clone(),
+                base_species,
+                family,
+                types,
+                base_stats,
+                raw_html,
+                simple_text,
+                stats_html,
+                tier,
+                set,
+            });
+        }
 
         Ok(DraftSet {
+            manifest,
             deduplicated_ids,
             id_to_item: items,
         })
     }
 
-    pub fn get_item_list(&self) -> Vec<DraftItemId> {
-        self.deduplicated_ids.values()
-            .map(|variants| variants.choose(&mut rand::thread_rng()).unwrap().clone())
+    /// Picks one item for each of the config's uniqueness keys, leaving out banned items
+    pub fn get_item_list<R: Rng>(&self, rng: &mut R, config: &LobbyConfig) -> Vec<DraftItemId> {
+        self.get_unique_items(config).values()
+            .map(|variants| *variants.choose(rng).unwrap())
             .collect()
     }
 
+    /// Groups the items that aren't banned by the config's uniqueness key. Ordered by key, so that the same random
+    /// number generator always picks the same items.
+    fn get_unique_items(&self, config: &LobbyConfig) -> BTreeMap<&String, Vec<DraftItemId>> {
+        let mut unique_items: BTreeMap<&String, Vec<DraftItemId>> = BTreeMap::new();
+        for &id in self.deduplicated_ids.values().flatten() {
+            let item = self.id_to_item.get(&id).unwrap();
+            if !item.is_banned(&config.banned) {
+                unique_items.entry(item.get_uniqueness_key(config.uniqueness)).or_default().push(id);
+            }
+        }
+        unique_items
+    }
+
+    /// The number of distinct items a draft with this config can draw from
+    pub fn num_unique_items(&self, config: &LobbyConfig) -> usize {
+        self.get_unique_items(config).len()
+    }
+
+    /// Checks that every name on the config's ban list matches something in the set, to catch typos
+    pub fn check_ban_list(&self, config: &LobbyConfig) -> DraftResult<()> {
+        match config.banned.iter().find(|name| !self.id_to_item.values().any(|item| item.has_name(name))) {
+            Some(name) => Err(DraftError::NotInSet(format!("Can't ban {name}, it isn't in this set"))),
+            None => Ok(()),
+        }
+    }
+
+    /// Groups the chosen items by the set's pack collation rules. Items can only fill the slots of their own tier.
+    pub fn get_pack_collation(&self, item_list: &[DraftItemId]) -> Vec<CollationRule> {
+        self.manifest.pack_collation.iter()
+            .map(|rule| CollationRule {
+                tier: rule.tier.clone(),
+                count: rule.count,
+                items: item_list.iter()
+                    .filter(|id| self.id_to_item.get(id).and_then(|item| item.get_tier()) == Some(&rule.tier))
+                    .copied()
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Checks that every lobby size the config allows can deal packs following the set's collation rules
+    pub fn check_pack_collation(&self, config: &LobbyConfig) -> DraftResult<()> {
+        if self.manifest.pack_collation.is_empty() || !config.format.deals_packs() {
+            return Ok(());
+        }
+        let slots_per_pack: usize = self.manifest.pack_collation.iter().map(|rule| rule.count).sum();
+        for num_players in 1..=config.capacity {
+            let (num_rounds, pack_size) = config.rounds_and_pack_size(num_players);
+            if slots_per_pack > pack_size {
+                return Err(DraftError::InvalidConfig(format!("This set puts {slots_per_pack} tiered items in every pack, but packs for {num_players} players only have {pack_size} items")));
+            }
+            let num_packs = num_players * num_rounds;
+            for rule in &self.manifest.pack_collation {
+                let available = self.num_unique_items_where(config, |tier| tier == Some(&rule.tier));
+                if num_packs * rule.count > available {
+                    return Err(DraftError::NotEnoughItems(format!("{num_players} players need {} items of tier {}, but this set only has {available}", num_packs * rule.count, rule.tier)));
+                }
+            }
+            let available = self.num_unique_items_where(config, |tier| !self.manifest.pack_collation.iter().any(|rule| tier == Some(&rule.tier)));
+            if num_packs * (pack_size - slots_per_pack) > available {
+                return Err(DraftError::NotEnoughItems(format!("{num_players} players need {} items outside the collated tiers, but this set only has {available}", num_packs * (pack_size - slots_per_pack))));
+            }
+        }
+        Ok(())
+    }
+
+    /// The number of unique items whose every variant has a tier matching the predicate, so that whichever variant is
+    /// chosen fits
+    fn num_unique_items_where<F: Fn(Option<&String>) -> bool>(&self, config: &LobbyConfig, predicate: F) -> usize {
+        self.get_unique_items(config).values()
+            .filter(|variants| variants.iter()
+                .all(|id| predicate(self.id_to_item.get(id).and_then(|item| item.get_tier()))))
+            .count()
+    }
+
+    pub fn get_manifest(&self) -> &SetManifest {
+        &self.manifest
+    }
+
     pub fn get_item_by_id(&self, id: &DraftItemId) -> Option<&DraftItem> {
         self.id_to_item.get(id)
     }
+
+    pub fn get_item_details(&self, id: &DraftItemId) -> Option<DraftItemDetails> {
+        let item = self.id_to_item.get(id)?;
+        Some(DraftItemDetails {
+            id: *id,
+            species: item.species.clone(),
+            raw: item.simple_text.clone(),
+            set: item.set.clone(),
+            types: item.types.clone(),
+            base_stats: item.base_stats,
+            tier: item.tier.clone(),
+        })
+    }
+
+    pub fn has_species(&self, species: &str) -> bool {
+        self.deduplicated_ids.keys().any(|set_species| set_species.eq_ignore_ascii_case(species))
+    }
+
+    /// The number of distinct items a draft can draw from, since only one variant of each species is used
+    pub fn num_unique_species(&self) -> usize {
+        self.deduplicated_ids.len()
+    }
 }
 
+/// Sets are shared with the lobbies that use them, so a reload doesn't change the items of a lobby in progress
 pub struct DraftDb {
-    sets: HashMap<String, DraftSet>,
+    dir_name: String,
+    sets: HashMap<String, Arc<DraftSet>>,
 }
 
 impl DraftDb {
-    pub fn from_folder(dir_name: &str) -> io::Result<DraftDb> {
+    /// Loads every set under `dir_name`. Broken sets are logged and left out of the database.
+    pub fn from_folder(dir_name: &str) -> Result<DraftDb, DraftDbError> {
+        let (db, problems) = DraftDb::load(dir_name)?;
+        for problem in problems {
+            match problem.set_is_usable() {
+                true => log::warn!("{problem}"),
+                false => log::error!("Failed to load set: {problem}"),
+            }
+        }
+        Ok(db)
+    }
+
+    /// Loads every set under `dir_name`, failing with every problem found if any set is broken
+    pub fn from_folder_strict(dir_name: &str) -> Result<DraftDb, Vec<DraftDbError>> {
+        let (db, problems) = DraftDb::load(dir_name).map_err(|e| vec![e])?;
+        match problems.is_empty() {
+            true => Ok(db),
+            false => Err(problems),
+        }
+    }
+
+    /// Loads a fresh copy of this database from the same folder. Fails if any set can't be loaded, so that a broken
+    /// edit doesn't make a set disappear from a running server.
+    pub fn reload(&self) -> Result<DraftDb, Vec<DraftDbError>> {
+        let (db, problems) = DraftDb::load(&self.dir_name).map_err(|e| vec![e])?;
+        let (warnings, errors): (Vec<DraftDbError>, Vec<DraftDbError>) = problems.into_iter()
+            .partition(|problem| problem.set_is_usable());
+        for warning in warnings {
+            log::warn!("{warning}");
+        }
+        match errors.is_empty() {
+            true => Ok(db),
+            false => Err(errors),
+        }
+    }
+
+    fn load(dir_name: &str) -> Result<(DraftDb, Vec<DraftDbError>), DraftDbError> {
         let mut sets = HashMap::new();
+        let mut problems = vec![];
+        let dex_dir = Path::new(dir_name).join(DEX_FOLDER);
+        let dex = Dex::from_folder(&dex_dir)
+            .map_err(|e| DraftDbError { set_name: None, path: dex_dir, kind: DraftDbErrorKind::Io(e) })?;
 
-        for entry in fs::read_dir(Path::new(dir_name))? {
-            let entry_path = entry?.path();
-            let set_name = entry_path.file_name().unwrap().to_str().unwrap().to_string();
+        for set_path in find_set_folders(dir_name)? {
+            let set_name = match set_path.file_name().and_then(|name| name.to_str()) {
+                Some(set_name) => set_name.to_string(),
+                None => {
+                    problems.push(DraftDbError { set_name: None, path: set_path, kind: DraftDbErrorKind::UnparsableName });
+                    continue;
+                }
+            };
             log::info!("Loading draft set {set_name}");
-            let set = DraftSet::from_folder(entry_path.to_str().unwrap());
-            match set {
-                Ok(s) => {sets.insert(set_name, s);}
-                Err(e) => log::error!("Failed to load {set_name}: {e}")
+            match DraftSet::from_folder(&set_name, &set_path, &dex) {
+                Ok(s) => {
+                    let default_config = s.manifest.default_lobby_config();
+                    let required_species = default_config.num_items_required();
+                    if s.num_unique_items(&default_config) < required_species {
+                        problems.push(DraftDbError::new(&set_name, &set_path.join(DRAFT_SETS_FILE), DraftDbErrorKind::TooFewSpecies {
+                            unique_species: s.num_unique_items(&default_config),
+                            required: required_species,
+                        }));
+                    }
+                    if let Err(e) = s.check_pack_collation(&default_config) {
+                        problems.push(DraftDbError::new(&set_name, &set_path.join(SET_MANIFEST_FILE), DraftDbErrorKind::UnfillablePackCollation(e)));
+                    }
+                    sets.insert(set_name, Arc::new(s));
+                }
+                Err(e) => problems.push(e),
             }
         }
 
-        Ok(DraftDb{
-            sets,
-        })
+        Ok((DraftDb { dir_name: dir_name.to_string(), sets }, problems))
+    }
+
+    pub fn get_set(&self, set_name: &String) -> Option<Arc<DraftSet>> {
+        self.sets.get(set_name).cloned()
+    }
+
+    /// Summarises every loaded set, ordered by display name
+    pub fn list_sets(&self) -> Vec<SetSummary> {
+        let mut sets: Vec<SetSummary> = self.sets.iter()
+            .map(|(set_name, set)| SetSummary {
+                name: set_name.clone(),
+                manifest: set.manifest.clone(),
+                item_count: set.id_to_item.len(),
+                species_count: set.num_unique_species(),
+            })
+            .collect();
+        sets.sort_by(|a, b| a.manifest.display_name.cmp(&b.manifest.display_name));
+        sets
+    }
+
+    /// Lists every item in a set, ordered by item id
+    pub fn list_set_items(&self, set_name: &String) -> Option<Vec<SetItemSummary>> {
+        let set = self.sets.get(set_name)?;
+        let mut items: Vec<SetItemSummary> = set.id_to_item.iter()
+            .map(|(&id, item)| SetItemSummary {
+                id,
+                species: item.get_species().clone(),
+                raw: item.get_raw().clone(),
+            })
+            .collect();
+        items.sort_by_key(|item| item.id);
+        Some(items)
+    }
+}
+
+/// Lists the draft set folders (those containing a `draft_sets.txt`) under a data folder
+pub fn find_set_folders(dir_name: &str) -> Result<Vec<PathBuf>, DraftDbError> {
+    let data_dir = Path::new(dir_name);
+    let io_error = |e| DraftDbError { set_name: None, path: data_dir.to_path_buf(), kind: DraftDbErrorKind::Io(e) };
+    let mut set_folders = vec![];
+    for entry in fs::read_dir(data_dir).map_err(io_error)? {
+        let entry_path = entry.map_err(io_error)?.path();
+        if entry_path.join(DRAFT_SETS_FILE).is_file() {
+            set_folders.push(entry_path);
+        }
+    }
+    Ok(set_folders)
+}
+
+/// Reads every `<item id>.html` file in a cache folder. A missing folder is an empty cache.
+fn read_html_cache(set_name: &str, cache_dir: &Path) -> Result<HashMap<DraftItemId, String>, DraftDbError> {
+    let mut cache = HashMap::new();
+    if !cache_dir.is_dir() {
+        return Ok(cache);
+    }
+    let io_error = |path: &Path, e| DraftDbError::new(set_name, path, DraftDbErrorKind::Io(e));
+    for entry in fs::read_dir(cache_dir).map_err(|e| io_error(cache_dir, e))? {
+        let entry_path = entry.map_err(|e| io_error(cache_dir, e))?.path();
+        let id: DraftItemId = match entry_path.file_name().and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(".html"))
+            .and_then(|id| id.parse().ok()) {
+            Some(id) => id,
+            None => return Err(DraftDbError::new(set_name, &entry_path, DraftDbErrorKind::UnparsableName)),
+        };
+        let html = fs::read_to_string(&entry_path).map_err(|e| io_error(&entry_path, e))?;
+        cache.insert(id, html);
+    }
+    Ok(cache)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use crate::draft_engine::DraftFormat;
+    use crate::test_fixtures::{load_set, sets_of, temp_data_folder, temp_folder, tiered_sets, write_set, DATA_FOLDER, TIERED_MANIFEST};
+
+    /// A data folder with a set too small for a full lobby, an empty set, and a set whose folder name isn't UTF-8
+    fn data_folder_with_problems(name: &str) -> String {
+        let data_dir = temp_data_folder(name);
+        write_set(&data_dir.join("small"), &sets_of(&["Garchomp", "Blissey"]), None);
+        write_set(&data_dir.join("empty"), "", None);
+        write_set(&data_dir.join(OsStr::from_bytes(b"bad-\xff")), &sets_of(&["Garchomp"]), None);
+        data_dir.to_str().unwrap().to_string()
     }
 
-    pub fn get_set(&self, set_name: &String) -> Option<&DraftSet> {
-        self.sets.get(set_name)
+    #[test]
+    fn loading_leaves_out_broken_sets() {
+        let db = DraftDb::from_folder(&data_folder_with_problems("lenient")).unwrap();
+        // Sets that are only too small for a full lobby are still loaded
+        assert!(db.get_set(&"small".to_string()).is_some());
+        assert!(db.get_set(&"empty".to_string()).is_none());
+        assert_eq!(db.list_sets().len(), 1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn strict_loading_fails_with_every_problem() {
+        let problems = match DraftDb::from_folder_strict(&data_folder_with_problems("strict")) {
+            Ok(_) => panic!("Loaded broken sets"),
+            Err(problems) => problems,
+        };
+        let mut found: Vec<(Option<&str>, &str)> = problems.iter()
+            .map(|problem| (problem.set_name.as_deref(), match problem.kind {
+                DraftDbErrorKind::TooFewSpecies { .. } => "too few species",
+                DraftDbErrorKind::EmptySet => "empty set",
+                DraftDbErrorKind::UnparsableName => "unparsable name",
+                _ => "something else",
+            }))
+            .collect();
+        found.sort();
+        assert_eq!(found, vec![(None, "unparsable name"), (Some("empty"), "empty set"), (Some("small"), "too few species")]);
+    }
+
+    #[test]
+    fn strict_loading_accepts_the_bundled_sets() {
+        assert!(DraftDb::from_folder_strict(DATA_FOLDER).is_ok());
+    }
+
+    #[test]
+    fn loading_without_a_dex_fails() {
+        let data_dir = temp_folder("no-dex");
+        let e = match DraftDb::from_folder(data_dir.to_str().unwrap()) {
+            Ok(_) => panic!("Loaded sets without a dex"),
+            Err(e) => e,
+        };
+        assert_eq!(e.path, data_dir.join(DEX_FOLDER));
+        assert!(matches!(e.kind, DraftDbErrorKind::Io(_)));
+    }
+
+    /// Two players drafting a pack of four each
+    fn two_packs_of_four() -> LobbyConfig {
+        LobbyConfig { capacity: 2, num_rounds: Some(1), pack_size: Some(4), ..LobbyConfig::default() }
+    }
+
+    #[test]
+    fn reads_tiers_from_sets_and_the_manifest() {
+        let set = load_set("tiers", &tiered_sets(), Some(TIERED_MANIFEST));
+        let tier = |id: DraftItemId| set.get_item_by_id(&id).unwrap().get_tier().cloned();
+        assert_eq!(tier(0), Some("S".to_string()));
+        assert_eq!(tier(3), None);
+        assert_eq!(tier(4), Some("A".to_string()));
+        assert_eq!(tier(9), None);
+    }
+
+    #[test]
+    fn collation_groups_the_chosen_items_by_tier() {
+        let set = load_set("collation", &tiered_sets(), Some(TIERED_MANIFEST));
+        let rules = set.get_pack_collation(&[2, 3, 4, 5, 9]);
+        let rules: Vec<(&str, usize, &Vec<DraftItemId>)> = rules.iter()
+            .map(|rule| (rule.tier.as_str(), rule.count, &rule.items))
+            .collect();
+        assert_eq!(rules, vec![("S", 1, &vec![2]), ("A", 2, &vec![4, 5])]);
+    }
+
+    #[test]
+    fn checks_the_collation_fits_every_lobby_size() {
+        let set = load_set("collation-check", &tiered_sets(), Some(TIERED_MANIFEST));
+        assert_eq!(set.check_pack_collation(&two_packs_of_four()), Ok(()));
+        // Garchomp might be drafted as its untiered set, so only two species can fill tier S slots
+        let three_players = LobbyConfig { capacity: 3, ..two_packs_of_four() };
+        assert!(matches!(set.check_pack_collation(&three_players), Err(DraftError::NotEnoughItems(_))));
+        let small_packs = LobbyConfig { pack_size: Some(2), ..two_packs_of_four() };
+        assert!(matches!(set.check_pack_collation(&small_packs), Err(DraftError::InvalidConfig(_))));
+        // Snake drafts don't deal packs, so aren't collated
+        let snake = LobbyConfig { format: DraftFormat::Snake, ..small_packs };
+        assert_eq!(set.check_pack_collation(&snake), Ok(()));
+    }
+
+    /// Two Rotom formes, two Eeveelutions, two of the Happiny family, Scyther and Scizor, two Garchomp sets and Lucario
+    fn formes_and_families() -> DraftSet {
+        let species = ["Rotom-Wash", "Rotom-Heat", "Vaporeon", "Jolteon", "Blissey", "Chansey", "Scyther", "Scizor",
+                       "Garchomp", "Garchomp", "Lucario"];
+        load_set("families", &sets_of(&species), None)
+    }
+
+    #[test]
+    fn item_lists_have_one_item_for_each_uniqueness_key() {
+        let set = formes_and_families();
+        for (uniqueness, num_keys) in [(UniquenessKey::Species, 10), (UniquenessKey::BaseSpecies, 9), (UniquenessKey::Family, 6)] {
+            let config = LobbyConfig { uniqueness, ..LobbyConfig::default() };
+            assert_eq!(set.num_unique_items(&config), num_keys);
+            for seed in 0..20 {
+                let items = set.get_item_list(&mut StdRng::seed_from_u64(seed), &config);
+                let mut keys: Vec<&String> = items.iter()
+                    .map(|id| set.get_item_by_id(id).unwrap().get_uniqueness_key(uniqueness))
+                    .collect();
+                keys.sort();
+                keys.dedup();
+                assert_eq!(keys.len(), num_keys);
+                assert_eq!(items.len(), num_keys);
+            }
+        }
+    }
+
+    #[test]
+    fn banned_items_are_never_listed() {
+        let set = formes_and_families();
+        // Bans match species, base species or family, whatever the case
+        let banned = ["rotom", "Eevee", "Scizor"].map(|name| name.to_string()).to_vec();
+        let config = LobbyConfig { banned, ..LobbyConfig::default() };
+        assert_eq!(set.check_ban_list(&config), Ok(()));
+        assert_eq!(set.num_unique_items(&config), 5);
+        for seed in 0..20 {
+            for id in set.get_item_list(&mut StdRng::seed_from_u64(seed), &config) {
+                let species = set.get_item_by_id(&id).unwrap().get_species();
+                assert!(!["Rotom-Wash", "Rotom-Heat", "Vaporeon", "Jolteon", "Scizor"].contains(&species.as_str()), "{species} was banned");
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_bans_that_match_nothing_in_the_set() {
+        let set = formes_and_families();
+        let config = LobbyConfig { banned: vec!["Rotom".to_string(), "Mewtwo".to_string()], ..LobbyConfig::default() };
+        assert_eq!(set.check_ban_list(&config), Err(DraftError::NotInSet("Can't ban Mewtwo, it isn't in this set".to_string())));
+    }
+}
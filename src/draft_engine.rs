@@ -1,12 +1,15 @@
 extern crate rand;
 
-use std::collections::{HashMap, VecDeque};
-use std::io;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
 
-use rand::RngCore;
+use rand::{RngCore, SeedableRng};
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
+use serde_derive::{Deserialize, Serialize};
 
 use crate::{lobby_manager, LobbyManagerResponse};
+use crate::bots::BotStrategy;
 
 
 pub type DraftItemId = u64;
@@ -14,36 +17,445 @@ pub type PackId = u64;
 pub type PlayerId = u32;
 pub type GameState = u64;
 pub type PackContents = Vec<DraftItemId>;
+pub type ForcedPicks = Vec<(PlayerId, DraftItemId)>;
+/// Chooses the item to pick from a pack for a player who ran out of time
+pub type AutoPick<'a> = dyn FnMut(&PackContents, &PlayerState, &mut StdRng) -> DraftItemId + 'a;
 pub type ResponseChannel = tokio::sync::oneshot::Sender<lobby_manager::LobbyManagerResponse>;
+pub type EventChannel = tokio::sync::mpsc::UnboundedSender<DraftEvent>;
+pub type DraftResult<T> = Result<T, DraftError>;
 
 pub const TIME_PER_PACK_ITEM_S: f64 = 25.0;
 pub const SLUSH_TIME_S: f64 = 2.0;
+pub const DEFAULT_LOBBY_CAPACITY: usize = 6;
+pub const MAX_LOBBY_CAPACITY: usize = 16;
+pub const DEFAULT_AUCTION_BUDGET: u32 = 100;
+pub const WINSTON_PLAYERS: usize = 2;
+pub const WINSTON_PILES: usize = 3;
+pub const GRID_WIDTH: usize = 3;
+pub const MAX_GRID_PLAYERS: usize = 3;
+
+/// Why a lobby couldn't do what it was asked to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DraftError {
+    LobbyNotFound,
+    UnknownSet(String),
+    PlayerNotFound,
+    LobbyFull,
+    NameTaken(String),
+    AlreadyStarted,
+    NotStarted,
+    NotYourTurn,
+    ItemNotInPack,
+    /// Something named in a request, like a banned or queued species, isn't in the lobby's set
+    NotInSet(String),
+    /// The set doesn't have enough items to deal the packs
+    NotEnoughItems(String),
+    /// The lobby's format doesn't work that way, e.g. picking single items in an auction
+    WrongFormat(String),
+    InvalidConfig(String),
+    /// Something the rules don't allow at this point in the draft, like a bid that's too low
+    InvalidMove(String),
+    /// The draft sets couldn't be reloaded
+    InvalidSets(String),
+    /// Something that shouldn't happen, like replaying events that don't fit the lobby
+    Internal(String),
+}
+
+impl DraftError {
+    /// A name for the kind of error, for clients to match on
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DraftError::LobbyNotFound => "lobby_not_found",
+            DraftError::UnknownSet(_) => "unknown_set",
+            DraftError::PlayerNotFound => "player_not_found",
+            DraftError::LobbyFull => "lobby_full",
+            DraftError::NameTaken(_) => "name_taken",
+            DraftError::AlreadyStarted => "already_started",
+            DraftError::NotStarted => "not_started",
+            DraftError::NotYourTurn => "not_your_turn",
+            DraftError::ItemNotInPack => "item_not_in_pack",
+            DraftError::NotInSet(_) => "not_in_set",
+            DraftError::NotEnoughItems(_) => "not_enough_items",
+            DraftError::WrongFormat(_) => "wrong_format",
+            DraftError::InvalidConfig(_) => "invalid_config",
+            DraftError::InvalidMove(_) => "invalid_move",
+            DraftError::InvalidSets(_) => "invalid_sets",
+            DraftError::Internal(_) => "internal",
+        }
+    }
+}
+
+impl fmt::Display for DraftError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DraftError::LobbyNotFound => write!(f, "Couldn't find lobby"),
+            DraftError::UnknownSet(set_name) => write!(f, "Unknown draft set {set_name}"),
+            DraftError::PlayerNotFound => write!(f, "Couldn't find player"),
+            DraftError::LobbyFull => write!(f, "Lobby full"),
+            DraftError::NameTaken(name) => write!(f, "Player name {name} has already joined"),
+            DraftError::AlreadyStarted => write!(f, "Game has already started"),
+            DraftError::NotStarted => write!(f, "Draft hasn't started yet"),
+            DraftError::NotYourTurn => write!(f, "It isn't this player's turn"),
+            DraftError::ItemNotInPack => write!(f, "Couldn't find item in pack"),
+            DraftError::NotInSet(message)
+            | DraftError::NotEnoughItems(message)
+            | DraftError::WrongFormat(message)
+            | DraftError::InvalidConfig(message)
+            | DraftError::InvalidMove(message)
+            | DraftError::InvalidSets(message)
+            | DraftError::Internal(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for DraftError {}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DraftFormat {
+    /// Each player opens a pack, picks from it, and passes it on. Passing direction alternates each round.
+    #[default]
+    Booster,
+    /// Players take turns picking from one shared pool, in order 1..N and then N..1
+    Snake,
+    /// Packs are opened face up one at a time, and players pick from each in snake order until it's empty.
+    /// The first pick moves one player along with each pack.
+    Rochester,
+    /// Players take turns nominating items from one shared pool, and everyone bids on them from a budget.
+    /// The highest bid wins once the bid timer runs out.
+    Auction,
+    /// A two player draft from a face-down stack and three face-down piles. Players look at each pile in turn, and
+    /// either take it or add a card from the stack to it and move on. Passing on the last pile takes a card from the
+    /// stack unseen.
+    Winston,
+    /// Items are dealt into 3x3 grids, and each player in turn takes a whole row or column of what's left.
+    /// The rest of each grid is discarded, and the first pick moves one player along with each grid.
+    Grid,
+}
+
+impl DraftFormat {
+    /// Whether the format deals items into packs of the lobby's pack size, rather than one big pool
+    pub fn deals_packs(&self) -> bool {
+        matches!(self, DraftFormat::Booster | DraftFormat::Rochester | DraftFormat::Grid)
+    }
+
+    /// Whether players draft by picking one item at a time, rather than bidding or taking piles or lines
+    pub fn picks_single_items(&self) -> bool {
+        matches!(self, DraftFormat::Booster | DraftFormat::Snake | DraftFormat::Rochester)
+    }
+}
+
+/// An entry in a player's pick queue: a particular item they've seen, or any set of a species
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PickPreference {
+    Item(DraftItemId),
+    Species(String),
+}
+
+/// The items a player wants picked for them, best first
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct PickQueue {
+    pub preferences: Vec<PickPreference>,
+    /// Picks the top queued item as soon as it's available, rather than when the player runs out of time
+    #[serde(default)]
+    pub pick_immediately: bool,
+}
+
+/// What counts as a duplicate when choosing a draft's items. Only one item is drafted for each key.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UniquenessKey {
+    /// Formes are different species, e.g. Rotom-Wash and Rotom-Heat can both be drafted
+    #[default]
+    Species,
+    /// Formes and regional variants count as their base species
+    BaseSpecies,
+    /// Every member of an evolutionary family counts as the same item, e.g. only one Eeveelution can be drafted
+    Family,
+}
+
+/// Every pack gets `count` items from `items`, which are the items of the draft in that tier
+#[derive(Debug, Clone)]
+pub struct CollationRule {
+    pub tier: String,
+    pub count: usize,
+    pub items: Vec<DraftItemId>,
+}
+
+/// A row or column of a grid draft's grid, counting from 0
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GridLine {
+    Row(usize),
+    Column(usize),
+}
+
+impl GridLine {
+    pub const ALL: [GridLine; 2 * GRID_WIDTH] = [GridLine::Row(0), GridLine::Row(1), GridLine::Row(2),
+        GridLine::Column(0), GridLine::Column(1), GridLine::Column(2)];
+
+    /// The indices of this line's cells in a grid laid out row by row
+    fn cells(&self) -> DraftResult<Vec<usize>> {
+        match *self {
+            GridLine::Row(row) if row < GRID_WIDTH => Ok((0..GRID_WIDTH).map(|column| row * GRID_WIDTH + column).collect()),
+            GridLine::Column(column) if column < GRID_WIDTH => Ok((0..GRID_WIDTH).map(|row| row * GRID_WIDTH + column).collect()),
+            _ => Err(DraftError::InvalidMove(format!("Grids only have {GRID_WIDTH} rows and columns"))),
+        }
+    }
+}
+
+/// The shape of a draft. Rounds and pack size that aren't set depend on how many players join,
+/// see `get_rounds_and_pack_sizes`. Snake and auction drafts use a pool big enough for each player to draft as many
+/// items as they would in a booster draft.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LobbyConfig {
+    #[serde(default)]
+    pub format: DraftFormat,
+    pub capacity: usize,
+    pub num_rounds: Option<usize>,
+    pub pack_size: Option<usize>,
+    pub time_per_pack_item_s: f64,
+    pub slush_time_s: f64,
+    #[serde(default = "default_auction_budget")]
+    pub auction_budget: u32,
+    #[serde(default)]
+    pub uniqueness: UniquenessKey,
+    /// Species, base species or families that are left out of the draft
+    #[serde(default)]
+    pub banned: Vec<String>,
+}
+
+fn default_auction_budget() -> u32 {
+    DEFAULT_AUCTION_BUDGET
+}
+
+impl Default for LobbyConfig {
+    fn default() -> LobbyConfig {
+        LobbyConfig {
+            format: DraftFormat::Booster,
+            capacity: DEFAULT_LOBBY_CAPACITY,
+            num_rounds: None,
+            pack_size: None,
+            time_per_pack_item_s: TIME_PER_PACK_ITEM_S,
+            slush_time_s: SLUSH_TIME_S,
+            auction_budget: DEFAULT_AUCTION_BUDGET,
+            uniqueness: UniquenessKey::Species,
+            banned: vec![],
+        }
+    }
+}
+
+/// Settings requested for a new lobby, applied over the set's default config
+#[derive(Debug, Clone, Default)]
+pub struct LobbyOptions {
+    pub format: Option<DraftFormat>,
+    pub capacity: Option<usize>,
+    pub num_rounds: Option<usize>,
+    pub pack_size: Option<usize>,
+    pub time_per_pack_item_s: Option<f64>,
+    pub auction_budget: Option<u32>,
+    pub uniqueness: Option<UniquenessKey>,
+    pub banned: Vec<String>,
+}
+
+impl LobbyConfig {
+    pub fn with_options(mut self, options: &LobbyOptions) -> LobbyConfig {
+        self.format = options.format.unwrap_or(self.format);
+        self.capacity = match (self.format, options.capacity) {
+            (_, Some(capacity)) => capacity,
+            (DraftFormat::Winston, None) => WINSTON_PLAYERS,
+            (DraftFormat::Grid, None) => self.capacity.min(MAX_GRID_PLAYERS),
+            (_, None) => self.capacity,
+        };
+        self.num_rounds = options.num_rounds.or(self.num_rounds);
+        self.pack_size = options.pack_size.or(self.pack_size);
+        self.time_per_pack_item_s = options.time_per_pack_item_s.unwrap_or(self.time_per_pack_item_s);
+        self.auction_budget = options.auction_budget.unwrap_or(self.auction_budget);
+        self.uniqueness = options.uniqueness.unwrap_or(self.uniqueness);
+        if !options.banned.is_empty() {
+            self.banned = options.banned.clone();
+        }
+        self
+    }
+
+    pub fn rounds_and_pack_size(&self, num_players: usize) -> (usize, usize) {
+        let (num_rounds, pack_size) = get_rounds_and_pack_sizes(num_players);
+        let pack_size = match self.format {
+            // A grid draft's packs are its grids
+            DraftFormat::Grid => GRID_WIDTH * GRID_WIDTH,
+            _ => self.pack_size.unwrap_or(pack_size),
+        };
+        (self.num_rounds.unwrap_or(num_rounds), pack_size)
+    }
+
+    /// The number of unique items needed to deal packs for any number of players up to the lobby's capacity
+    pub fn num_items_required(&self) -> usize {
+        (1..=self.capacity)
+            .map(|num_players| {
+                let (num_rounds, pack_size) = self.rounds_and_pack_size(num_players);
+                num_players * num_rounds * pack_size
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The most items any one player can end up with, for any number of players up to the lobby's capacity
+    pub fn max_items_per_player(&self) -> usize {
+        (1..=self.capacity)
+            .map(|num_players| {
+                let (num_rounds, pack_size) = self.rounds_and_pack_size(num_players);
+                num_rounds * pack_size
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn validate(&self) -> DraftResult<()> {
+        if self.capacity == 0 || self.capacity > MAX_LOBBY_CAPACITY {
+            return Err(DraftError::InvalidConfig(format!("Lobby capacity must be between 1 and {MAX_LOBBY_CAPACITY}")));
+        }
+        if self.num_rounds == Some(0) || self.pack_size == Some(0) {
+            return Err(DraftError::InvalidConfig("Drafts need at least one round and one item per pack".to_string()));
+        }
+        // Written so that NaN timers are rejected too
+        let timers_are_valid = self.time_per_pack_item_s > 0.0 && self.slush_time_s >= 0.0;
+        if !timers_are_valid {
+            return Err(DraftError::InvalidConfig("Pick timers must be positive".to_string()));
+        }
+        if self.format == DraftFormat::Winston && self.capacity != WINSTON_PLAYERS {
+            return Err(DraftError::InvalidConfig(format!("Winston drafts are for exactly {WINSTON_PLAYERS} players")));
+        }
+        if self.format == DraftFormat::Grid && self.capacity > MAX_GRID_PLAYERS {
+            return Err(DraftError::InvalidConfig(format!("Grid drafts are for up to {MAX_GRID_PLAYERS} players")));
+        }
+        // Every bid leaves at least a point for each open slot, so a budget this big can always fill a team
+        if self.format == DraftFormat::Auction && (self.auction_budget as usize) < self.max_items_per_player() {
+            return Err(DraftError::InvalidConfig(format!("An auction budget must be at least {}, one point per item", self.max_items_per_player())));
+        }
+        Ok(())
+    }
+}
 
 pub struct UpdateListener {
     response_channel: Option<ResponseChannel>,
     game_state: GameState,
 }
 
+/// Something that happened in a lobby, as told to one player over an event stream
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum DraftEvent {
+    Joined { player_name: String },
+    Started { draft_order: Vec<String> },
+    /// The player has a new pack, pile, pool or grid to pick from
+    Pack { items: Vec<DraftItemId> },
+    /// The player picked an item, or had one picked for them
+    Picked { item_id: DraftItemId },
+    Round { round: usize, total_rounds: usize },
+    Finished,
+}
+
+impl DraftEvent {
+    /// The type of the event, as in its `event` field
+    pub fn name(&self) -> &'static str {
+        match self {
+            DraftEvent::Joined { .. } => "joined",
+            DraftEvent::Started { .. } => "started",
+            DraftEvent::Pack { .. } => "pack",
+            DraftEvent::Picked { .. } => "picked",
+            DraftEvent::Round { .. } => "round",
+            DraftEvent::Finished => "finished",
+        }
+    }
+}
+
+/// Like an `UpdateListener`, but stays open for the whole draft and is sent what changed rather than just that
+/// something did. Remembers how much of the draft it's been told about.
+struct EventStream {
+    player_id: PlayerId,
+    channel: EventChannel,
+    players_seen: usize,
+    started_seen: bool,
+    round_seen: Option<usize>,
+    picks_seen: usize,
+    pack_state_seen: Option<GameState>,
+}
+
 pub struct PlayerState {
     pub allocated_items: Vec<DraftItemId>,
     pub pending_packs: VecDeque<PackId>,
+    /// Points left to bid with in an auction draft
+    pub budget: u32,
+    pub pick_queue: PickQueue,
+}
+
+/// The item up for auction, and the highest bid on it so far
+#[derive(Debug, Clone)]
+pub struct AuctionBid {
+    pub item_id: DraftItemId,
+    pub nominated_by: PlayerId,
+    pub high_bidder: PlayerId,
+    pub amount: u32,
+}
+
+/// What a player can see of a Winston draft
+#[derive(Serialize, Debug)]
+pub struct WinstonPiles {
+    pub stack_size: usize,
+    pub pile_sizes: Vec<usize>,
+    /// The pile this player is looking at, counting from 1
+    pub current_pile: Option<usize>,
+}
+
+/// What happened when an auction draft's timer ran out
+#[derive(Debug)]
+pub enum AuctionTimeout {
+    /// The nominating player ran out of time, so the first item in the pool was put up for them
+    Nominated { player_id: PlayerId, item_id: DraftItemId },
+    /// Bidding closed, and the highest bidder won the item
+    Sold { player_id: PlayerId, item_id: DraftItemId, price: u32 },
+}
+
+/// What happened when a grid draft's timer ran out: the player whose turn it was took a line for them
+#[derive(Debug)]
+pub struct GridTimeout {
+    pub player_id: PlayerId,
+    pub line: GridLine,
 }
 
 pub struct DraftState {
+    format: DraftFormat,
     players: HashMap<PlayerId, PlayerState>,
+    pack_size: usize,
+    picks_made: usize,
     turn_order: Vec<PlayerId>,
     packs_by_round: Vec<HashMap<PackId, PackContents>>,
     current_round_idx: usize,
     draft_direction: bool,
+    current_bid: Option<AuctionBid>,
+    // Counts nominations, bids and sales in an auction, and piles taken or passed in a Winston draft, so that a
+    // deadline can tell whether the draft has moved on
+    turns_taken: usize,
+    // The current grid of a grid draft, row by row. Cells are emptied as lines are taken.
+    grid: Vec<Option<DraftItemId>>,
 }
 
 pub struct DraftLobby {
     set_name: String,
-    player_capacity: usize,
+    config: LobbyConfig,
+    // Everything random about the draft comes from this, so the same seed and picks always give the same draft
+    seed: u64,
+    rng: StdRng,
     draft_state: Option<DraftState>,
     joined_players: HashMap<PlayerId, String>,
+    join_order: Vec<PlayerId>,
+    bots: HashMap<PlayerId, BotStrategy>,
     listeners: HashMap<PlayerId, Vec<UpdateListener>>,
+    event_streams: Vec<EventStream>,
     round_deadlines: HashMap<usize, HashMap<usize, std::time::Instant>>,
+    // Pick queues set in the lobby, before the draft has started. They move into the player states on start.
+    pick_queues: HashMap<PlayerId, PickQueue>,
 }
 
 #[derive(Debug)]
@@ -54,7 +466,7 @@ pub struct DraftDeadline {
 }
 
 impl UpdateListener {
-    pub fn flush(&mut self) -> io::Result<()> {
+    pub fn flush(&mut self) -> DraftResult<()> {
         let channel = self.response_channel.take();
         if channel.is_none() {
             return Ok(());
@@ -63,43 +475,114 @@ impl UpdateListener {
             Ok(_) => Ok(()),
             Err(_) => {
                 // No need to log this: actually happens if users close browser.
-                return Err(io::Error::new(io::ErrorKind::ConnectionRefused, "Receiver has dropped"));
+                return Err(DraftError::Internal("Receiver has dropped".to_string()));
             }
         }
     }
 }
 
 impl DraftLobby {
-    pub fn new(set_name: String, player_capacity: usize) -> DraftLobby {
+    pub fn new(set_name: String, config: LobbyConfig, seed: u64) -> DraftLobby {
         return DraftLobby {
             set_name,
-            player_capacity,
+            config,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
             draft_state: None,
             joined_players: HashMap::new(),
+            join_order: vec![],
+            bots: HashMap::new(),
             listeners: HashMap::new(),
+            event_streams: vec![],
             round_deadlines: HashMap::new(),
+            pick_queues: HashMap::new(),
         };
     }
 
-    pub fn add_player(&mut self, name: String) -> io::Result<PlayerId> {
+    pub fn add_player(&mut self, name: String) -> DraftResult<PlayerId> {
+        let id = self.generate_player_id();
+        self.add_player_with_id(id, name)?;
+        Ok(id)
+    }
+
+    /// Adds a player under a known id, e.g. when restoring a lobby from the event log
+    pub fn add_player_with_id(&mut self, id: PlayerId, name: String) -> DraftResult<()> {
         // Validation
         if self.draft_state.is_some() {
-            return Err(io::Error::new(io::ErrorKind::ConnectionRefused, "Game has already started"));
+            return Err(DraftError::AlreadyStarted);
         }
         if self.joined_players.values().find(|&x| x == &name).is_some() {
-            return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("Player name {} has already joined", name)));
+            return Err(DraftError::NameTaken(name));
         }
-        if self.joined_players.len() >= self.player_capacity {
-            return Err(io::Error::new(io::ErrorKind::ConnectionRefused, "Lobby full"));
+        if self.joined_players.len() >= self.config.capacity {
+            return Err(DraftError::LobbyFull);
+        }
+        if self.joined_players.contains_key(&id) {
+            return Err(DraftError::Internal(format!("Player id {} has already joined", id)));
         }
-        let id = self.generate_player_id();
         self.joined_players.insert(id, name);
+        self.join_order.push(id);
         self.listeners.insert(id, vec![]);
         self.check_listeners();
-        return Ok(id);
+        Ok(())
+    }
+
+    /// Adds a bot that picks with the given strategy, named after the strategy
+    pub fn add_bot(&mut self, strategy: BotStrategy) -> DraftResult<(PlayerId, String)> {
+        let id = self.generate_player_id();
+        let name = format!("{} {}", strategy.display_name(), self.bots.len() + 1);
+        self.add_bot_with_id(id, name.clone(), strategy)?;
+        Ok((id, name))
+    }
+
+    /// Adds a bot under a known id, e.g. when restoring a lobby from the event log
+    pub fn add_bot_with_id(&mut self, id: PlayerId, name: String, strategy: BotStrategy) -> DraftResult<()> {
+        if !self.config.format.picks_single_items() {
+            return Err(DraftError::WrongFormat("Bots can only play booster, snake and Rochester drafts".to_string()));
+        }
+        self.add_player_with_id(id, name)?;
+        self.bots.insert(id, strategy);
+        Ok(())
+    }
+
+    pub fn get_bots(&self) -> &HashMap<PlayerId, BotStrategy> {
+        &self.bots
     }
 
-    pub fn add_listener(&mut self, player_id: PlayerId, game_state: GameState, response_channel: ResponseChannel) -> io::Result<()> {
+    /// Replaces a player's pick queue. Only drafts where players pick one item at a time have pick queues. Queues
+    /// set before the draft starts are kept on the lobby until `start`.
+    pub fn set_pick_queue(&mut self, player_id: PlayerId, pick_queue: PickQueue) -> DraftResult<()> {
+        if !self.config.format.picks_single_items() {
+            return Err(DraftError::WrongFormat("Only booster, snake and Rochester drafts have pick queues".to_string()));
+        }
+        match self.draft_state.as_mut() {
+            Some(draft_state) => {
+                let player_state = draft_state.players.get_mut(&player_id)
+                    .ok_or(DraftError::PlayerNotFound)?;
+                player_state.pick_queue = pick_queue;
+            }
+            None => {
+                if !self.joined_players.contains_key(&player_id) {
+                    return Err(DraftError::PlayerNotFound);
+                }
+                self.pick_queues.insert(player_id, pick_queue);
+            }
+        }
+        Ok(())
+    }
+
+    /// A player's pick queue, whether or not the draft has started.
+    pub fn get_pick_queue(&self, player_id: &PlayerId) -> Option<PickQueue> {
+        match self.draft_state.as_ref() {
+            Some(draft_state) => draft_state.players.get(player_id).map(|state| state.pick_queue.clone()),
+            None if self.joined_players.contains_key(player_id) => {
+                Some(self.pick_queues.get(player_id).cloned().unwrap_or_default())
+            }
+            None => None,
+        }
+    }
+
+    pub fn add_listener(&mut self, player_id: PlayerId, game_state: GameState, response_channel: ResponseChannel) -> DraftResult<()> {
         let current_state = self.compute_state(&player_id);
         let mut listener = UpdateListener { response_channel: Some(response_channel), game_state };
         if current_state != game_state {
@@ -115,23 +598,103 @@ impl DraftLobby {
         Ok(())
     }
 
-    pub fn start(&mut self, item_list: &Vec<DraftItemId>) -> io::Result<DraftDeadline> {
+    /// Sends everything that has happened in the lobby so far to the channel, and then each new event until the draft
+    /// finishes, when the channel is dropped. Anyone can watch a lobby, but only its players hear about packs and picks.
+    pub fn add_event_stream(&mut self, player_id: PlayerId, channel: EventChannel) {
+        let mut event_stream = EventStream {
+            player_id,
+            channel,
+            players_seen: 0,
+            started_seen: false,
+            round_seen: None,
+            picks_seen: 0,
+            pack_state_seen: None,
+        };
+        if self.update_event_stream(&mut event_stream).is_ok() && !self.draft_is_finished() {
+            self.event_streams.push(event_stream);
+        }
+    }
+
+    /// Deals the packs for a draft without starting it. Players draft in the order they joined.
+    /// Packs follow the collation rules if there are any, except in formats that draft from one big pool.
+    pub fn deal_packs(&mut self, item_list: &Vec<DraftItemId>, collation: &[CollationRule]) -> DraftResult<(Vec<PlayerId>, Vec<PackContents>)> {
         if self.draft_state.is_some() {
-            return Err(io::Error::new(io::ErrorKind::AlreadyExists, "Game has already started"));
+            return Err(DraftError::AlreadyStarted);
         }
         if self.joined_players.is_empty() {
-            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Lobby has no players"));
+            return Err(DraftError::InvalidMove("Lobby has no players".to_string()));
+        }
+        let player_ids: Vec<PlayerId> = self.join_order.clone();
+        let (num_packs, num_items_in_pack) = self.get_pack_layout(player_ids.len());
+        let packs = match collation.is_empty() || !self.config.format.deals_packs() {
+            true => make_random_packs(num_packs, num_items_in_pack, item_list, &mut self.rng)?,
+            false => make_collated_packs(num_packs, num_items_in_pack, item_list, collation, &mut self.rng)?,
+        };
+        Ok((player_ids, packs))
+    }
+
+    /// How many packs a draft between this many players uses, and how big they are.
+    /// The pool of a snake or auction draft is one big pack.
+    fn get_pack_layout(&self, num_players: usize) -> (usize, usize) {
+        let (num_rounds, pack_size) = self.config.rounds_and_pack_size(num_players);
+        match self.config.format.deals_packs() {
+            true => (num_rounds * num_players, pack_size),
+            false => (1, num_players * num_rounds * pack_size),
+        }
+    }
+
+    /// Starts the draft with packs from `deal_packs`. Starting a lobby from the same turn order and packs always
+    /// hands out the same packs to the same players.
+    pub fn start(&mut self, turn_order: Vec<PlayerId>, packs: Vec<PackContents>) -> DraftResult<DraftDeadline> {
+        if self.draft_state.is_some() {
+            return Err(DraftError::AlreadyStarted);
+        }
+        if turn_order.len() != self.joined_players.len() || turn_order.iter().any(|id| !self.joined_players.contains_key(id)) {
+            return Err(DraftError::Internal("Turn order doesn't match the joined players".to_string()));
+        }
+        if self.config.format == DraftFormat::Winston && turn_order.len() != WINSTON_PLAYERS {
+            return Err(DraftError::InvalidMove(format!("Winston drafts need exactly {WINSTON_PLAYERS} players")));
+        }
+        let (num_packs, num_items_in_pack) = self.get_pack_layout(turn_order.len());
+        if packs.len() != num_packs || packs.iter().any(|pack| pack.len() != num_items_in_pack) {
+            return Err(DraftError::Internal("Packs don't match the number of players".to_string()));
         }
-        let player_ids: Vec<PlayerId> = self.joined_players.keys().cloned().collect();
-        let (num_rounds, num_items_in_pack) = get_rounds_and_pack_sizes(player_ids.len());
-        let packs = make_random_packs(num_rounds * player_ids.len(), num_items_in_pack, item_list)?;
-        self.draft_state = Some(DraftState::new(player_ids, packs, num_rounds));
+        let (num_rounds, pack_size) = self.config.rounds_and_pack_size(turn_order.len());
+        let budget = self.config.auction_budget;
+        let queues = std::mem::take(&mut self.pick_queues);
+        self.draft_state = Some(match self.config.format {
+            DraftFormat::Booster => DraftState::new(DraftFormat::Booster, turn_order, packs, num_rounds, pack_size, budget, queues),
+            // Every Rochester pack and every grid is a round of its own
+            DraftFormat::Rochester | DraftFormat::Grid => DraftState::new(self.config.format, turn_order.clone(), packs, num_rounds * turn_order.len(), pack_size, budget, queues),
+            // Snake and auction drafts are one long round, where each player drafts a whole team
+            format => DraftState::new(format, turn_order, packs, 1, num_rounds * pack_size, budget, queues),
+        });
         self.generate_deadlines();
         self.check_listeners();
         let first_deadline = self.get_deadline_for(0, 0);
         Ok(first_deadline.unwrap())
     }
 
+    /// Restarts the clock for the current round after restoring a lobby, giving the players who are furthest behind
+    /// a full pick timer. Returns the next deadline to enforce, if the draft is still running.
+    pub fn resume_deadlines(&mut self) -> Option<DraftDeadline> {
+        if self.draft_is_finished() {
+            return None;
+        }
+        let draft_state = self.draft_state.as_ref()?;
+        if draft_state.format != DraftFormat::Booster {
+            return self.next_turn_deadline();
+        }
+        let pack_size = draft_state.get_pack_size();
+        let round_idx = draft_state.current_round_idx;
+        let pick_idx = draft_state.players.values()
+            .map(|player_state| player_state.allocated_items.len())
+            .min()?
+            .saturating_sub(pack_size * round_idx);
+        self.generate_deadlines_from(pick_idx);
+        self.get_deadline_for(round_idx, pick_idx)
+    }
+
     fn get_deadline_for(&self, round_number: usize, pick_number: usize) -> Option<DraftDeadline> {
         self.round_deadlines.get(&round_number)
             .map(|x| x.get(&pick_number))
@@ -139,8 +702,36 @@ impl DraftLobby {
             .map(|x| DraftDeadline { round_number, pick_number, deadline: x.clone() })
     }
 
+    /// Every player in the lobby, in the order they joined
+    pub fn get_player_ids(&self) -> &Vec<PlayerId> {
+        &self.join_order
+    }
+
     pub fn get_player_names(&self) -> Vec<String> {
-        self.joined_players.values().cloned().collect()
+        self.join_order.iter()
+            .map(|player_id| self.joined_players.get(player_id).unwrap().clone())
+            .collect()
+    }
+
+    pub fn get_capacity(&self) -> usize {
+        self.config.capacity
+    }
+
+    pub fn get_format(&self) -> DraftFormat {
+        self.config.format
+    }
+
+    pub fn get_config(&self) -> &LobbyConfig {
+        &self.config
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The lobby's random number generator, for choosing the items to deal into packs
+    pub fn get_rng(&mut self) -> &mut StdRng {
+        &mut self.rng
     }
 
     pub fn get_draft_order(&self) -> Vec<String> {
@@ -177,7 +768,14 @@ impl DraftLobby {
             None => return None,
         };
         let draft_state = self.draft_state.as_ref().unwrap();
-        let (_, pack_size) = get_rounds_and_pack_sizes(draft_state.turn_order.len());
+        match draft_state.format {
+            // Only the player whose turn it is has a deadline
+            DraftFormat::Snake | DraftFormat::Rochester | DraftFormat::Winston | DraftFormat::Grid if draft_state.players.get(player_id)?.pending_packs.is_empty() => return None,
+            // Everyone in an auction can bid, so everyone sees the clock
+            DraftFormat::Snake | DraftFormat::Rochester | DraftFormat::Winston | DraftFormat::Grid | DraftFormat::Auction => return self.round_deadlines.get(&0)?.get(&draft_state.turn_idx()),
+            DraftFormat::Booster => (),
+        }
+        let pack_size = draft_state.get_pack_size();
         let items_allocated_this_round = items_allocated_to_player % pack_size;
         self.round_deadlines.get(&draft_state.current_round_idx)
             .map(|x| x.get(&items_allocated_this_round))
@@ -191,18 +789,255 @@ impl DraftLobby {
         return self.draft_state.as_ref().unwrap().players.get(player_id);
     }
 
-    pub fn make_pick(&mut self, player_id: PlayerId, picked_item_id: DraftItemId) -> io::Result<Option<DraftDeadline>> {
+    pub fn make_pick(&mut self, player_id: PlayerId, picked_item_id: DraftItemId) -> DraftResult<Option<DraftDeadline>> {
         if self.draft_state.is_none() {
-            return Err(io::Error::new(io::ErrorKind::NotFound, "Draft hasn't started yet"));
+            return Err(DraftError::NotStarted);
+        }
+        match self.config.format {
+            DraftFormat::Auction => return Err(DraftError::WrongFormat("Items in an auction draft are won by bidding".to_string())),
+            DraftFormat::Winston => return Err(DraftError::WrongFormat("Items in a Winston draft are taken a pile at a time".to_string())),
+            DraftFormat::Grid => return Err(DraftError::WrongFormat("Items in a grid draft are taken a line at a time".to_string())),
+            _ => (),
         }
         self.draft_state.as_mut().unwrap().pick(player_id, picked_item_id)?;
-        let deadline = self.maybe_start_new_round()?; // todo is there a deadline in here?
+        let deadline = match self.config.format {
+            DraftFormat::Booster => self.maybe_start_new_round()?, // todo is there a deadline in here?
+            _ => self.next_turn()?,
+        };
+        self.check_listeners();
+        Ok(deadline)
+    }
+
+    /// The shared pool in formats where every player can see it, whether or not it's their turn
+    pub fn get_public_pool(&self) -> Option<&PackContents> {
+        let draft_state = self.draft_state.as_ref()?;
+        match draft_state.format {
+            // Winston piles are face down, and grids are laid out with `get_grid`
+            DraftFormat::Booster | DraftFormat::Winston | DraftFormat::Grid => None,
+            DraftFormat::Snake | DraftFormat::Auction => draft_state.get_pack_contents(&0),
+            // Each round has a single pack, which shares the round's index
+            DraftFormat::Rochester => draft_state.get_pack_contents(&(draft_state.current_round_idx as PackId)),
+        }
+    }
+
+    /// The player whose turn it is in a turn-based draft, if anyone's
+    pub fn get_current_player(&self) -> Option<PlayerId> {
+        let draft_state = self.draft_state.as_ref()?;
+        if draft_state.format == DraftFormat::Booster {
+            return None;
+        }
+        draft_state.turn_order.iter()
+            .find(|player_id| !draft_state.players.get(player_id).unwrap().pending_packs.is_empty())
+            .copied()
+    }
+
+    /// Puts an item from the pool up for auction, with an opening bid of one point from the nominating player
+    pub fn nominate(&mut self, player_id: PlayerId, item_id: DraftItemId) -> DraftResult<Option<DraftDeadline>> {
+        let draft_state = self.get_draft_state_for(DraftFormat::Auction)?;
+        draft_state.nominate(player_id, item_id)?;
+        let deadline = self.next_turn_deadline();
+        self.check_listeners();
+        Ok(deadline)
+    }
+
+    /// Raises the bid on the item up for auction, which restarts the bid timer
+    pub fn bid(&mut self, player_id: PlayerId, amount: u32) -> DraftResult<Option<DraftDeadline>> {
+        let draft_state = self.get_draft_state_for(DraftFormat::Auction)?;
+        draft_state.bid(player_id, amount)?;
+        let deadline = self.next_turn_deadline();
+        self.check_listeners();
+        Ok(deadline)
+    }
+
+    /// Gives the item up for auction to the highest bidder. Returns who won it, and what they paid.
+    pub fn close_auction(&mut self) -> DraftResult<(PlayerId, DraftItemId, u32)> {
+        let draft_state = self.get_draft_state_for(DraftFormat::Auction)?;
+        let sale = draft_state.close_auction()?;
+        self.next_turn_deadline();
+        self.check_listeners();
+        Ok(sale)
+    }
+
+    /// When the clock runs out in an auction, either sells the item up for auction or nominates one for the
+    /// player who's run out of time. Like other turn-based deadlines, it does nothing if the auction has moved on.
+    pub fn enforce_auction_deadline(&mut self, turn_idx: usize) -> DraftResult<(Option<DraftDeadline>, Option<AuctionTimeout>)> {
+        let draft_state = self.get_draft_state_for(DraftFormat::Auction)?;
+        if draft_state.turn_idx() != turn_idx || draft_state.draft_is_done() {
+            return Ok((None, None));
+        }
+        let timeout = match draft_state.current_bid {
+            Some(_) => {
+                let (player_id, item_id, price) = draft_state.close_auction()?;
+                AuctionTimeout::Sold { player_id, item_id, price }
+            }
+            None => {
+                let nominator = draft_state.players.iter()
+                    .find(|(_, player_state)| !player_state.pending_packs.is_empty())
+                    .map(|(&player_id, _)| player_id);
+                let player_id = match nominator {
+                    Some(player_id) => player_id,
+                    None => return Ok((None, None)),
+                };
+                let &item_id = draft_state.get_pack_contents(&0).unwrap().first().unwrap();
+                draft_state.nominate(player_id, item_id)?;
+                AuctionTimeout::Nominated { player_id, item_id }
+            }
+        };
+        let new_deadline = self.next_turn_deadline();
+        self.check_listeners();
+        Ok((new_deadline, Some(timeout)))
+    }
+
+    fn get_draft_state_for(&mut self, format: DraftFormat) -> DraftResult<&mut DraftState> {
+        if self.config.format != format {
+            return Err(DraftError::WrongFormat(format!("This isn't a {format:?} draft")));
+        }
+        self.draft_state.as_mut()
+            .ok_or(DraftError::NotStarted)
+    }
+
+    /// Takes the Winston pile the player is looking at, and passes the turn on
+    pub fn take_pile(&mut self, player_id: PlayerId) -> DraftResult<Option<DraftDeadline>> {
+        let draft_state = self.get_draft_state_for(DraftFormat::Winston)?;
+        draft_state.take_pile(player_id)?;
+        let deadline = self.next_turn_deadline();
         self.check_listeners();
         Ok(deadline)
     }
 
+    /// Adds a card from the stack to the Winston pile the player is looking at, and moves them on to the next pile
+    pub fn pass_pile(&mut self, player_id: PlayerId) -> DraftResult<Option<DraftDeadline>> {
+        let draft_state = self.get_draft_state_for(DraftFormat::Winston)?;
+        draft_state.pass_pile(player_id)?;
+        let deadline = self.next_turn_deadline();
+        self.check_listeners();
+        Ok(deadline)
+    }
+
+    /// Makes a player who's run out of time take the Winston pile they're looking at. Returns who that was, if the
+    /// draft hadn't already moved on.
+    pub fn enforce_winston_deadline(&mut self, turn_idx: usize) -> DraftResult<(Option<DraftDeadline>, Option<PlayerId>)> {
+        let current_player = self.get_current_player();
+        let draft_state = self.get_draft_state_for(DraftFormat::Winston)?;
+        if draft_state.turn_idx() != turn_idx || draft_state.draft_is_done() {
+            return Ok((None, None));
+        }
+        let player_id = match current_player {
+            Some(player_id) => player_id,
+            None => return Ok((None, None)),
+        };
+        draft_state.take_pile(player_id)?;
+        let new_deadline = self.next_turn_deadline();
+        self.check_listeners();
+        Ok((new_deadline, Some(player_id)))
+    }
+
+    /// Takes a row or column of the current grid, and passes the turn on
+    pub fn take_line(&mut self, player_id: PlayerId, line: GridLine) -> DraftResult<Option<DraftDeadline>> {
+        let draft_state = self.get_draft_state_for(DraftFormat::Grid)?;
+        draft_state.take_line(player_id, line)?;
+        let deadline = self.next_turn()?;
+        self.check_listeners();
+        Ok(deadline)
+    }
+
+    /// Makes a player who's run out of time take the line of the grid with the most items left in it.
+    /// Returns who that was and the line they took, if the draft hadn't already moved on.
+    pub fn enforce_grid_deadline(&mut self, turn_idx: usize) -> DraftResult<(Option<DraftDeadline>, Option<GridTimeout>)> {
+        let current_player = self.get_current_player();
+        let draft_state = self.get_draft_state_for(DraftFormat::Grid)?;
+        if draft_state.turn_idx() != turn_idx || draft_state.draft_is_done() {
+            return Ok((None, None));
+        }
+        let player_id = match current_player {
+            Some(player_id) => player_id,
+            None => return Ok((None, None)),
+        };
+        let fullest_line = GridLine::ALL.iter()
+            .max_by_key(|line| line.cells().unwrap().iter().filter(|&&cell| draft_state.grid[cell].is_some()).count())
+            .copied()
+            .unwrap();
+        draft_state.take_line(player_id, fullest_line)?;
+        let new_deadline = self.next_turn()?;
+        self.check_listeners();
+        Ok((new_deadline, Some(GridTimeout { player_id, line: fullest_line })))
+    }
+
+    /// The current grid of a grid draft, row by row, with empty cells where lines have been taken
+    pub fn get_grid(&self) -> Option<&Vec<Option<DraftItemId>>> {
+        let draft_state = self.draft_state.as_ref()?;
+        match draft_state.format {
+            DraftFormat::Grid => Some(&draft_state.grid),
+            _ => None,
+        }
+    }
+
+    pub fn get_winston_piles(&self, player_id: &PlayerId) -> Option<WinstonPiles> {
+        let draft_state = self.draft_state.as_ref()?;
+        if draft_state.format != DraftFormat::Winston {
+            return None;
+        }
+        Some(WinstonPiles {
+            stack_size: draft_state.get_pack_contents(&0).map_or(0, |stack| stack.len()),
+            pile_sizes: (1..=WINSTON_PILES)
+                .map(|pile_id| draft_state.get_pack_contents(&(pile_id as PackId)).map_or(0, |pile| pile.len()))
+                .collect(),
+            current_pile: draft_state.players.get(player_id)?.pending_packs.front().map(|&pile_id| pile_id as usize),
+        })
+    }
+
+    pub fn get_current_bid(&self) -> Option<&AuctionBid> {
+        self.draft_state.as_ref()?.current_bid.as_ref()
+    }
+
+    /// The most the player can bid on the item up for auction right now, if they're allowed to bid at all
+    pub fn get_max_bid_for_player(&self, player_id: &PlayerId) -> Option<u32> {
+        let draft_state = self.draft_state.as_ref()?;
+        let current_bid = draft_state.current_bid.as_ref()?;
+        if &current_bid.high_bidder == player_id {
+            return None;
+        }
+        draft_state.max_bid(player_id).filter(|&max_bid| max_bid > current_bid.amount)
+    }
+
+    /// Each player's name and remaining budget, in turn order. Empty unless this is an auction draft.
+    pub fn get_budgets(&self) -> Vec<(String, u32)> {
+        match self.draft_state.as_ref() {
+            Some(draft_state) if draft_state.format == DraftFormat::Auction => draft_state.turn_order.iter()
+                .map(|player_id| (self.joined_players.get(player_id).unwrap().clone(), draft_state.players.get(player_id).unwrap().budget))
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    pub fn get_player_name(&self, player_id: &PlayerId) -> Option<&String> {
+        self.joined_players.get(player_id)
+    }
+
+    /// Moves a turn-based draft on after a pick, opening the next pack of a Rochester draft or the next grid of a grid
+    /// draft once the last is done
+    fn next_turn(&mut self) -> DraftResult<Option<DraftDeadline>> {
+        let draft_state = self.draft_state.as_mut().unwrap();
+        let has_rounds = matches!(draft_state.format, DraftFormat::Rochester | DraftFormat::Grid);
+        if has_rounds && draft_state.round_is_done() && draft_state.rounds_remaining() > 0 {
+            draft_state.start_next_round()?;
+        }
+        Ok(self.next_turn_deadline())
+    }
+
+    /// Starts the clock for the next turn of a turn-based draft
+    fn next_turn_deadline(&mut self) -> Option<DraftDeadline> {
+        if self.draft_is_finished() {
+            return None;
+        }
+        let turn_idx = self.draft_state.as_ref()?.turn_idx();
+        self.generate_deadlines_from(turn_idx);
+        self.get_deadline_for(0, turn_idx)
+    }
+
     pub fn get_current_pack_contents_for_player(&self, player_id: &PlayerId) -> Option<&PackContents> {
-        if self.draft_state.is_none() {
+        // Grid drafts are picked from a line at a time, see `get_grid`
+        if self.draft_state.is_none() || self.config.format == DraftFormat::Grid {
             return None;
         }
         let pack_id = self.get_player_draft_state(player_id)
@@ -218,6 +1053,7 @@ impl DraftLobby {
 
 
     fn generate_player_id(&self) -> PlayerId {
+        // Player ids work as passwords for the draft page, so they mustn't be predictable from the (public) seed
         let id: PlayerId = rand::thread_rng().next_u32();
         if self.joined_players.contains_key(&id) {
             self.generate_player_id()
@@ -226,7 +1062,7 @@ impl DraftLobby {
         }
     }
 
-    fn maybe_start_new_round(&mut self) -> io::Result<Option<DraftDeadline>> {
+    fn maybe_start_new_round(&mut self) -> DraftResult<Option<DraftDeadline>> {
         if self.draft_state.is_none() {
             return Ok(None);
         }
@@ -242,40 +1078,69 @@ impl DraftLobby {
         Ok(None)
     }
 
-    pub fn enforce_deadline(&mut self, round_idx: usize, pick_idx: usize) -> io::Result<Option<DraftDeadline>> {
-        if self.draft_state.is_none() { return Ok(None); };
+    /// Makes a pick for every player who's behind the given deadline. Returns the next deadline, and the picks made.
+    pub fn enforce_deadline(&mut self, round_idx: usize, pick_idx: usize, auto_pick: &mut AutoPick) -> DraftResult<(Option<DraftDeadline>, ForcedPicks)> {
+        if self.draft_state.is_none() { return Ok((None, vec![])); };
+        match self.config.format {
+            DraftFormat::Booster => (),
+            DraftFormat::Snake | DraftFormat::Rochester => return self.enforce_turn_deadline(pick_idx, auto_pick),
+            DraftFormat::Auction => return Err(DraftError::WrongFormat("Auction deadlines sell items rather than forcing picks".to_string())),
+            DraftFormat::Winston => return Err(DraftError::WrongFormat("Winston deadlines take piles rather than forcing picks".to_string())),
+            DraftFormat::Grid => return Err(DraftError::WrongFormat("Grid deadlines take lines rather than forcing picks".to_string())),
+        }
         let draft_state = self.draft_state.as_mut().unwrap();
-        let (_, pack_size) = get_rounds_and_pack_sizes(draft_state.turn_order.len());
+        let pack_size = draft_state.get_pack_size();
         let minimum_allocated = pack_size * round_idx + pick_idx + 1;
         let round_idx = draft_state.current_round_idx;
 
-        let mut picks_to_make: Vec<(PlayerId, DraftItemId)> = vec!();
+        let mut picks_to_make: ForcedPicks = vec!();
 
-        for (&player_id, player_state) in &draft_state.players {
+        // Players are picked for in turn order, so that picks using the lobby's random number generator are reproducible
+        for &player_id in &draft_state.turn_order {
+            let player_state = draft_state.players.get(&player_id).unwrap();
             // Currently assumes this will be called each time, so only checks once
             if player_state.allocated_items.len() < minimum_allocated && !player_state.pending_packs.is_empty() {
                 let pack_id = player_state.pending_packs.get(0).unwrap();
                 let pack_contents = draft_state
                     .packs_by_round.get(round_idx).unwrap()
                     .get(pack_id).unwrap();
-                let &random_pick = pack_contents.get(0).unwrap();
-                picks_to_make.push((player_id, random_pick));
+                picks_to_make.push((player_id, auto_pick(pack_contents, player_state, &mut self.rng)));
             }
         }
 
-        for (player_id, random_pick) in picks_to_make {
-            draft_state.pick(player_id, random_pick)?;
+        for &(player_id, forced_pick) in &picks_to_make {
+            draft_state.pick(player_id, forced_pick)?;
         }
         self.check_listeners();
         if self.draft_is_finished() {
-            return Ok(None);
+            return Ok((None, picks_to_make));
         }
         let new_round = self.maybe_start_new_round()?;
         self.check_listeners();
         if new_round.is_some() {
-            return Ok(new_round);
+            return Ok((new_round, picks_to_make));
+        }
+        Ok((self.get_deadline_for(round_idx, pick_idx + 1), picks_to_make))
+    }
+
+    /// In turn-based drafts, a deadline is for a single turn. It only forces a pick if that turn is still going.
+    fn enforce_turn_deadline(&mut self, turn_idx: usize, auto_pick: &mut AutoPick) -> DraftResult<(Option<DraftDeadline>, ForcedPicks)> {
+        let draft_state = self.draft_state.as_mut().unwrap();
+        if draft_state.turn_idx() != turn_idx || draft_state.draft_is_done() {
+            return Ok((None, vec![]));
         }
-        Ok(self.get_deadline_for(round_idx, pick_idx + 1))
+        let current_player = draft_state.players.iter()
+            .find(|(_, player_state)| !player_state.pending_packs.is_empty());
+        let (player_id, player_state) = match current_player {
+            Some((&player_id, player_state)) => (player_id, player_state),
+            None => return Ok((None, vec![])),
+        };
+        let pack_contents = draft_state.get_pack_contents(player_state.pending_packs.front().unwrap()).unwrap();
+        let forced_pick = auto_pick(pack_contents, player_state, &mut self.rng);
+        draft_state.pick(player_id, forced_pick)?;
+        let new_deadline = self.next_turn()?;
+        self.check_listeners();
+        Ok((new_deadline, vec![(player_id, forced_pick)]))
     }
 
     fn check_listeners(&mut self) {
@@ -296,23 +1161,98 @@ impl DraftLobby {
             }
             listener_list.retain(|listener| listener.game_state == current_state && !draft_done);
         }
+
+        let mut event_streams = std::mem::take(&mut self.event_streams);
+        event_streams.retain_mut(|event_stream| self.update_event_stream(event_stream).is_ok() && !draft_done);
+        self.event_streams = event_streams;
+    }
+
+    /// Sends a stream the events it hasn't been sent yet. Fails if nobody is listening to it any more.
+    fn update_event_stream(&self, event_stream: &mut EventStream) -> Result<(), tokio::sync::mpsc::error::SendError<DraftEvent>> {
+        let mut events = vec![];
+        for player_id in &self.join_order[event_stream.players_seen..] {
+            events.push(DraftEvent::Joined { player_name: self.joined_players.get(player_id).unwrap().clone() });
+        }
+        event_stream.players_seen = self.join_order.len();
+
+        if let Some(draft_state) = self.draft_state.as_ref() {
+            if !event_stream.started_seen {
+                let draft_order = draft_state.turn_order.iter()
+                    .map(|player_id| self.joined_players.get(player_id).unwrap().clone())
+                    .collect();
+                events.push(DraftEvent::Started { draft_order });
+                event_stream.started_seen = true;
+            }
+            if event_stream.round_seen != Some(draft_state.current_round_idx) {
+                events.push(DraftEvent::Round { round: draft_state.current_round_idx + 1, total_rounds: draft_state.num_rounds() });
+                event_stream.round_seen = Some(draft_state.current_round_idx);
+            }
+            if let Some(player_state) = draft_state.players.get(&event_stream.player_id) {
+                for &item_id in &player_state.allocated_items[event_stream.picks_seen..] {
+                    events.push(DraftEvent::Picked { item_id });
+                }
+                event_stream.picks_seen = player_state.allocated_items.len();
+            }
+            // A player's state changes whenever they get something new to pick from
+            let pack = match self.config.format {
+                DraftFormat::Grid if self.get_current_player() == Some(event_stream.player_id) => self.get_grid()
+                    .map(|grid| grid.iter().flatten().copied().collect()),
+                _ => self.get_current_pack_contents_for_player(&event_stream.player_id).cloned(),
+            };
+            let pack_state = self.compute_state(&event_stream.player_id);
+            if let Some(items) = pack.filter(|_| event_stream.pack_state_seen != Some(pack_state)) {
+                events.push(DraftEvent::Pack { items });
+                event_stream.pack_state_seen = Some(pack_state);
+            }
+            if self.draft_is_finished() {
+                events.push(DraftEvent::Finished);
+            }
+        }
+
+        for event in events {
+            event_stream.channel.send(event)?;
+        }
+        Ok(())
     }
 
     fn generate_deadlines(&mut self) {
+        self.generate_deadlines_from(0);
+    }
+
+    fn generate_deadlines_from(&mut self, first_pick_idx: usize) {
         if self.draft_state.is_none() {
             log::error!("Tried to generate deadlines before creating draft");
             return;
         }
         let draft_state = self.draft_state.as_ref().unwrap();
-        let (_, pack_size) = get_rounds_and_pack_sizes(draft_state.turn_order.len());
+        let pack_size = draft_state.get_pack_size();
         let now = std::time::Instant::now();
         let mut deadlines: HashMap<usize, std::time::Instant> = HashMap::new();
-        for i in 0..pack_size {
+        if draft_state.format != DraftFormat::Booster {
+            // A turn gets as long as the first pick from a booster pack, and a Rochester pick as long as a pick from a
+            // booster pack with as many items left. Bidding on an item gets one item's worth of time.
+            let (_, booster_pack_size) = self.config.rounds_and_pack_size(draft_state.turn_order.len());
+            let items_worth_of_time = match (draft_state.format, &draft_state.current_bid) {
+                (DraftFormat::Auction, Some(_)) => 1,
+                // Looking at a Winston pile gets as long as a booster pick from a pack the size of the pile
+                (DraftFormat::Winston, _) => draft_state.players.values()
+                    .find_map(|player_state| player_state.pending_packs.front())
+                    .and_then(|pile_id| draft_state.get_pack_contents(pile_id))
+                    .map_or(0, |pile| pile.len()),
+                (DraftFormat::Rochester | DraftFormat::Grid, _) => draft_state.get_pack_contents(&(draft_state.current_round_idx as PackId)).map_or(0, |pack| pack.len()),
+                _ => booster_pack_size,
+            };
+            let time_for_this_turn = std::time::Duration::from_secs_f64(self.config.slush_time_s + self.config.time_per_pack_item_s * items_worth_of_time as f64);
+            deadlines.insert(first_pick_idx, now + time_for_this_turn);
+            self.round_deadlines.insert(0, deadlines);
+            return;
+        }
+        for i in first_pick_idx..pack_size {
             let items_in_this_pack = pack_size - i;
-            let time_for_this_pack = std::time::Duration::from_secs_f64(SLUSH_TIME_S) + std::time::Duration::from_secs_f64(TIME_PER_PACK_ITEM_S * items_in_this_pack as f64);
-            let last_deadline = match i {
-                0 => &now,
-                _ => deadlines.get(&(i - 1)).unwrap()
+            let time_for_this_pack = std::time::Duration::from_secs_f64(self.config.slush_time_s) + std::time::Duration::from_secs_f64(self.config.time_per_pack_item_s * items_in_this_pack as f64);
+            let last_deadline = match i == first_pick_idx {
+                true => &now,
+                false => deadlines.get(&(i - 1)).unwrap()
             };
             let deadline = *last_deadline + time_for_this_pack;
             deadlines.insert(i, deadline);
@@ -331,7 +1271,11 @@ impl DraftLobby {
         }
         let player_data = player_data.unwrap();
         let has_pending_packs = !player_data.pending_packs.is_empty() as u64;
-        let num_drafted_so_far = player_data.allocated_items.len() as u64;
+        // Everyone can see the pool in turn-based drafts, so every turn changes every player's view
+        let num_drafted_so_far = match self.config.format {
+            DraftFormat::Booster => player_data.allocated_items.len() as u64,
+            _ => self.draft_state.as_ref().unwrap().turn_idx() as u64,
+        };
         return num_drafted_so_far + 1024 * has_pending_packs + 1024 * 1024 * num_players;
     }
 
@@ -352,20 +1296,24 @@ impl DraftLobby {
         let total_rounds = draft_state.num_rounds();
         let current_round = draft_state.current_round_idx + 1;
         let pack_size = draft_state.get_pack_size();
-        let pick_num = 1 + draft_state.players.get(player_id)
-            .map(|p| p.allocated_items.len() % pack_size)
-            .unwrap();
+        let pick_num = match draft_state.format {
+            // Counts picks from the open pack, since players don't get the same number of picks from every pack
+            DraftFormat::Rochester => 1 + pack_size - self.get_public_pool().map_or(0, |pack| pack.len()),
+            _ => 1 + draft_state.players.get(player_id)
+                .map(|p| p.allocated_items.len() % pack_size)
+                .unwrap(),
+        };
         return Some((current_round, total_rounds, pick_num, pack_size))
     }
 }
 
-pub fn make_random_packs(num_packs: usize, pack_size: usize, item_list: &Vec<DraftItemId>) -> io::Result<Vec<PackContents>> {
+pub fn make_random_packs(num_packs: usize, pack_size: usize, item_list: &Vec<DraftItemId>, rng: &mut StdRng) -> DraftResult<Vec<PackContents>> {
     let num_unique_items_required = num_packs * pack_size;
     if num_unique_items_required > item_list.len() {
-        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Requested number of packs: {}. Number of unique items: {}", num_unique_items_required, item_list.len())));
+        return Err(DraftError::NotEnoughItems(format!("Requested number of packs: {}. Number of unique items: {}", num_unique_items_required, item_list.len())));
     }
     let mut item_indices: Vec<usize> = (0..item_list.len()).collect();
-    item_indices.shuffle(&mut rand::thread_rng());
+    item_indices.shuffle(rng);
 
 
     let mut completed_packs = vec!();
@@ -384,8 +1332,39 @@ pub fn make_random_packs(num_packs: usize, pack_size: usize, item_list: &Vec<Dra
     Ok(completed_packs)
 }
 
+/// Deals packs the way booster packs are collated: each pack gets the number of items each rule asks for from that
+/// rule's tier, and the rest of the pack is filled from items in none of the rules' tiers
+pub fn make_collated_packs(num_packs: usize, pack_size: usize, item_list: &[DraftItemId], collation: &[CollationRule], rng: &mut StdRng) -> DraftResult<Vec<PackContents>> {
+    let slots_per_pack: usize = collation.iter().map(|rule| rule.count).sum();
+    if slots_per_pack > pack_size {
+        return Err(DraftError::InvalidConfig(format!("Pack collation needs {} items per pack, but packs only have {}", slots_per_pack, pack_size)));
+    }
+    let mut completed_packs: Vec<PackContents> = vec![vec!(); num_packs];
+    for rule in collation {
+        let num_items_required = num_packs * rule.count;
+        if num_items_required > rule.items.len() {
+            return Err(DraftError::NotEnoughItems(format!("Requested number of tier {} items: {}. Number of unique items: {}", rule.tier, num_items_required, rule.items.len())));
+        }
+        let mut tier_items = rule.items.clone();
+        tier_items.shuffle(rng);
+        for (i, item) in tier_items.into_iter().take(num_items_required).enumerate() {
+            completed_packs[i / rule.count].push(item);
+        }
+    }
+
+    let tiered_items: HashSet<DraftItemId> = collation.iter().flat_map(|rule| rule.items.iter().copied()).collect();
+    let remaining_items: Vec<DraftItemId> = item_list.iter().filter(|id| !tiered_items.contains(*id)).copied().collect();
+    let filler_packs = make_random_packs(num_packs, pack_size - slots_per_pack, &remaining_items, rng)?;
+    for (pack, filler) in completed_packs.iter_mut().zip(filler_packs) {
+        pack.extend(filler);
+        // So that tiered items don't always open at the front of the pack
+        pack.shuffle(rng);
+    }
+    Ok(completed_packs)
+}
+
 pub fn get_rounds_and_pack_sizes(num_players: usize) -> (usize, usize) {
-    // max capacity: 6 players
+    // The defaults for lobbies that don't configure their rounds or pack size
     let (num_rounds, pack_size) = match num_players {
         0 => (0, 0),
         1 => (1, 6),
@@ -393,24 +1372,28 @@ pub fn get_rounds_and_pack_sizes(num_players: usize) -> (usize, usize) {
         3 => (2, 6),
         4 => (2, 6),
         5 => (2, 6),
-        6 => (2, 8),
-        _ => (0, 0)
+        _ => (2, 8)
     };
     return (num_rounds, pack_size);
 }
 
 impl DraftState {
-    pub fn new(player_ids: Vec<PlayerId>, mut packs: Vec<PackContents>, num_rounds: usize) -> DraftState {
+    pub fn new(format: DraftFormat, player_ids: Vec<PlayerId>, mut packs: Vec<PackContents>, num_rounds: usize, pack_size: usize, budget: u32, mut pick_queues: HashMap<PlayerId, PickQueue>) -> DraftState {
         let mut players = HashMap::new();
         for player_id in &player_ids {
-            players.insert(player_id.clone(), PlayerState { allocated_items: vec!(), pending_packs: VecDeque::new() });
+            let pick_queue = pick_queues.remove(player_id).unwrap_or_default();
+            players.insert(player_id.clone(), PlayerState { allocated_items: vec!(), pending_packs: VecDeque::new(), budget, pick_queue });
         }
 
         let mut packs_by_round = vec!();
         let mut current_pack_id = 0;
+        let packs_per_round = match format {
+            DraftFormat::Booster => player_ids.len(),
+            DraftFormat::Snake | DraftFormat::Rochester | DraftFormat::Auction | DraftFormat::Winston | DraftFormat::Grid => 1,
+        };
         for _ in 0..num_rounds {
             let mut round_packs = HashMap::new();
-            for _ in 0..player_ids.len() {
+            for _ in 0..packs_per_round {
                 let pack = packs.pop().unwrap();
                 round_packs.insert(current_pack_id, pack);
                 current_pack_id += 1;
@@ -420,23 +1403,29 @@ impl DraftState {
         // Could add a check here for packs remaining
 
         let mut draft = DraftState {
+            format,
             players,
+            pack_size,
+            picks_made: 0,
             turn_order: player_ids,
             packs_by_round,
             current_round_idx: 0,
             draft_direction: true,
+            current_bid: None,
+            turns_taken: 0,
+            grid: vec![],
         };
         draft.set_initial_round_packs().unwrap();
         draft
     }
 
-    pub fn pick(&mut self, player_id: PlayerId, picked_item_id: DraftItemId) -> io::Result<()> {
+    pub fn pick(&mut self, player_id: PlayerId, picked_item_id: DraftItemId) -> DraftResult<()> {
         if !self.players.contains_key(&player_id) {
-            return Err(io::Error::new(io::ErrorKind::NotFound, "Couldn't find player"));
+            return Err(DraftError::PlayerNotFound);
         }
         let player_state = self.players.get_mut(&player_id).unwrap();
         if player_state.pending_packs.is_empty() {
-            return Err(io::Error::new(io::ErrorKind::NotFound, "Player had no packs"));
+            return Err(DraftError::NotYourTurn);
         }
         let pack_id = player_state.pending_packs.front().unwrap();
 
@@ -445,16 +1434,25 @@ impl DraftState {
             .get_mut(pack_id).unwrap();
         let picked_item_idx = selected_pack.iter().position(|x| x == &picked_item_id);
         if picked_item_idx.is_none() {
-            return Err(io::Error::new(io::ErrorKind::NotFound, "Couldn't find item in pack"));
+            return Err(DraftError::ItemNotInPack);
         }
         let picked_item_idx = picked_item_idx.unwrap();
 
         player_state.allocated_items.push(picked_item_id);
         let pack_id = player_state.pending_packs.pop_front().unwrap();
         selected_pack.remove(picked_item_idx);
+        self.picks_made += 1;
+        let picks_from_pack = self.pack_size.saturating_sub(selected_pack.len());
 
         if !selected_pack.is_empty() {
-            let next_player_id = self.next_player_from(player_id)?;
+            let next_player_id = match self.format {
+                DraftFormat::Booster => self.next_player_from(player_id)?,
+                DraftFormat::Snake => self.advance_snake_turn(self.picks_made, 0),
+                DraftFormat::Rochester => self.advance_snake_turn(picks_from_pack, self.current_round_idx),
+                DraftFormat::Auction => return Err(DraftError::WrongFormat("Items in an auction draft are won by bidding".to_string())),
+                DraftFormat::Winston => return Err(DraftError::WrongFormat("Items in a Winston draft are taken a pile at a time".to_string())),
+                DraftFormat::Grid => return Err(DraftError::WrongFormat("Items in a grid draft are taken a line at a time".to_string())),
+            };
             let next_player_state = self.players.get_mut(&next_player_id).unwrap();
             next_player_state.pending_packs.push_back(pack_id);
         }
@@ -468,14 +1466,221 @@ impl DraftState {
         all_packs_empty
     }
 
-    pub fn start_next_round(&mut self) -> io::Result<()> {
+    pub fn start_next_round(&mut self) -> DraftResult<()> {
         self.current_round_idx += 1;
         self.draft_direction = !self.draft_direction;
         self.set_initial_round_packs()
     }
 
 
-    pub fn next_player_from(&self, player_id: PlayerId) -> io::Result<PlayerId> {
+    /// Moves a snake draft on to turn `turn_idx`, counting from the player `first_player_idx` places along the turn
+    /// order, and returns whose turn it is. The direction flips after each pass.
+    fn advance_snake_turn(&mut self, turn_idx: usize, first_player_idx: usize) -> PlayerId {
+        let num_players = self.turn_order.len();
+        let position = turn_idx % num_players;
+        self.draft_direction = (turn_idx / num_players).is_multiple_of(2);
+        let offset = match self.draft_direction {
+            true => position,
+            false => num_players - 1 - position,
+        };
+        self.turn_order[(first_player_idx + offset) % num_players]
+    }
+
+    /// Which turn a turn-based draft is on. Every nomination, bid and sale is a turn in an auction, and every pile
+    /// taken or passed is a turn in a Winston draft.
+    fn turn_idx(&self) -> usize {
+        match self.format {
+            DraftFormat::Auction | DraftFormat::Winston | DraftFormat::Grid => self.turns_taken,
+            _ => self.picks_made,
+        }
+    }
+
+    /// The most a player can bid while keeping a point for each other open slot on their team.
+    /// Players with full teams can't bid.
+    pub fn max_bid(&self, player_id: &PlayerId) -> Option<u32> {
+        let player_state = self.players.get(player_id)?;
+        let open_slots = self.pack_size.saturating_sub(player_state.allocated_items.len()) as u32;
+        if open_slots == 0 {
+            return None;
+        }
+        Some(player_state.budget.saturating_sub(open_slots - 1))
+    }
+
+    pub fn nominate(&mut self, player_id: PlayerId, item_id: DraftItemId) -> DraftResult<()> {
+        if self.current_bid.is_some() {
+            return Err(DraftError::InvalidMove("An item is already up for auction".to_string()));
+        }
+        let player_state = self.players.get_mut(&player_id)
+            .ok_or(DraftError::PlayerNotFound)?;
+        if player_state.pending_packs.is_empty() {
+            return Err(DraftError::NotYourTurn);
+        }
+        let pack_id = *player_state.pending_packs.front().unwrap();
+        if !self.get_pack_contents(&pack_id).is_some_and(|pool| pool.contains(&item_id)) {
+            return Err(DraftError::ItemNotInPack);
+        }
+        self.players.get_mut(&player_id).unwrap().pending_packs.pop_front();
+        self.current_bid = Some(AuctionBid { item_id, nominated_by: player_id, high_bidder: player_id, amount: 1 });
+        self.turns_taken += 1;
+        Ok(())
+    }
+
+    pub fn bid(&mut self, player_id: PlayerId, amount: u32) -> DraftResult<()> {
+        let max_bid = self.max_bid(&player_id);
+        let current_bid = self.current_bid.as_mut()
+            .ok_or(DraftError::InvalidMove("Nothing is up for auction".to_string()))?;
+        if current_bid.high_bidder == player_id {
+            return Err(DraftError::InvalidMove("Player already has the highest bid".to_string()));
+        }
+        if amount <= current_bid.amount {
+            return Err(DraftError::InvalidMove(format!("Bids must be more than {}", current_bid.amount)));
+        }
+        match max_bid {
+            None => return Err(DraftError::InvalidMove("Player's team is already full".to_string())),
+            Some(max_bid) if amount > max_bid => return Err(DraftError::InvalidMove(format!("Player can bid at most {max_bid}"))),
+            Some(_) => (),
+        }
+        current_bid.high_bidder = player_id;
+        current_bid.amount = amount;
+        self.turns_taken += 1;
+        Ok(())
+    }
+
+    /// Sells the item up for auction, then passes the nomination on to the next player with space on their team
+    pub fn close_auction(&mut self) -> DraftResult<(PlayerId, DraftItemId, u32)> {
+        let sold = self.current_bid.take()
+            .ok_or(DraftError::InvalidMove("Nothing is up for auction".to_string()))?;
+        let pool = self.packs_by_round
+            .get_mut(self.current_round_idx).unwrap()
+            .get_mut(&0).unwrap();
+        pool.retain(|&item_id| item_id != sold.item_id);
+        let pool_is_empty = pool.is_empty();
+
+        let winner = self.players.get_mut(&sold.high_bidder).unwrap();
+        winner.allocated_items.push(sold.item_id);
+        winner.budget -= sold.amount;
+        self.picks_made += 1;
+        self.turns_taken += 1;
+
+        if !pool_is_empty {
+            let mut next_player_id = self.next_player_from(sold.nominated_by)?;
+            for _ in 0..self.turn_order.len() {
+                if self.max_bid(&next_player_id).is_some() {
+                    break;
+                }
+                next_player_id = self.next_player_from(next_player_id)?;
+            }
+            self.players.get_mut(&next_player_id).unwrap().pending_packs.push_back(0);
+        }
+        Ok((sold.high_bidder, sold.item_id, sold.amount))
+    }
+
+    /// Takes what's left of a line of the current grid. Once every player has taken a line, the rest of the grid is
+    /// discarded.
+    pub fn take_line(&mut self, player_id: PlayerId, line: GridLine) -> DraftResult<()> {
+        let player_state = self.players.get(&player_id)
+            .ok_or(DraftError::PlayerNotFound)?;
+        let &pack_id = player_state.pending_packs.front()
+            .ok_or(DraftError::NotYourTurn)?;
+        let cells = line.cells()?;
+        if cells.iter().all(|&cell| self.grid[cell].is_none()) {
+            return Err(DraftError::InvalidMove("That line has already been taken".to_string()));
+        }
+        let taken_items: Vec<DraftItemId> = cells.iter().filter_map(|&cell| self.grid[cell].take()).collect();
+
+        let pack = self.packs_by_round[self.current_round_idx].get_mut(&pack_id).unwrap();
+        pack.retain(|item_id| !taken_items.contains(item_id));
+        let player_state = self.players.get_mut(&player_id).unwrap();
+        player_state.pending_packs.pop_front();
+        self.picks_made += taken_items.len();
+        player_state.allocated_items.extend(taken_items);
+        self.turns_taken += 1;
+
+        let first_player = self.turn_order[self.current_round_idx % self.turn_order.len()];
+        let next_player_id = self.next_player_from(player_id)?;
+        if next_player_id == first_player || self.grid.iter().all(|cell| cell.is_none()) {
+            self.packs_by_round[self.current_round_idx].get_mut(&pack_id).unwrap().clear();
+            self.grid.iter_mut().for_each(|cell| *cell = None);
+        } else {
+            self.players.get_mut(&next_player_id).unwrap().pending_packs.push_back(pack_id);
+        }
+        Ok(())
+    }
+
+    /// Takes every card in the Winston pile the player is looking at, refills the pile from the stack, and passes the
+    /// turn to the other player
+    pub fn take_pile(&mut self, player_id: PlayerId) -> DraftResult<()> {
+        let pile_id = self.get_winston_pile_for(&player_id)?;
+        let pile = std::mem::take(self.packs_by_round[0].get_mut(&pile_id).unwrap());
+        if let Some(card) = self.draw_from_stack() {
+            self.packs_by_round[0].get_mut(&pile_id).unwrap().push(card);
+        }
+        let player_state = self.players.get_mut(&player_id).unwrap();
+        player_state.pending_packs.clear();
+        self.picks_made += pile.len();
+        player_state.allocated_items.extend(pile);
+        self.end_winston_turn(player_id)
+    }
+
+    /// Adds a card from the stack to the Winston pile the player is looking at, and moves them on to the next pile
+    /// with cards in it. Passing on the last pile takes the top card of the stack instead.
+    pub fn pass_pile(&mut self, player_id: PlayerId) -> DraftResult<()> {
+        let pile_id = self.get_winston_pile_for(&player_id)?;
+        let next_pile_id = (pile_id + 1..=WINSTON_PILES as PackId)
+            .find(|next_pile_id| self.get_pack_contents(next_pile_id).is_some_and(|pile| !pile.is_empty()));
+        let stack_size = self.get_pack_contents(&0).map_or(0, |stack| stack.len());
+        // The last pile has to be taken unless there are cards left both to add to it and to take from the stack
+        if next_pile_id.is_none() && stack_size < 2 {
+            return Err(DraftError::InvalidMove("This is the last pile, so it has to be taken".to_string()));
+        }
+        if let Some(card) = self.draw_from_stack() {
+            self.packs_by_round[0].get_mut(&pile_id).unwrap().push(card);
+        }
+        let player_state = self.players.get_mut(&player_id).unwrap();
+        player_state.pending_packs.clear();
+        match next_pile_id {
+            Some(next_pile_id) => {
+                player_state.pending_packs.push_back(next_pile_id);
+                self.turns_taken += 1;
+                Ok(())
+            }
+            None => {
+                let card = self.draw_from_stack().unwrap();
+                self.players.get_mut(&player_id).unwrap().allocated_items.push(card);
+                self.picks_made += 1;
+                self.end_winston_turn(player_id)
+            }
+        }
+    }
+
+    fn get_winston_pile_for(&self, player_id: &PlayerId) -> DraftResult<PackId> {
+        let player_state = self.players.get(player_id)
+            .ok_or(DraftError::PlayerNotFound)?;
+        player_state.pending_packs.front().copied()
+            .ok_or(DraftError::NotYourTurn)
+    }
+
+    fn draw_from_stack(&mut self) -> Option<DraftItemId> {
+        let stack = self.packs_by_round[0].get_mut(&0).unwrap();
+        match stack.is_empty() {
+            true => None,
+            false => Some(stack.remove(0)),
+        }
+    }
+
+    /// Hands the Winston turn to the other player, who starts at the first pile with cards in it
+    fn end_winston_turn(&mut self, player_id: PlayerId) -> DraftResult<()> {
+        self.turns_taken += 1;
+        let next_player_id = self.next_player_from(player_id)?;
+        let first_pile_id = (1..=WINSTON_PILES as PackId)
+            .find(|pile_id| self.get_pack_contents(pile_id).is_some_and(|pile| !pile.is_empty()));
+        if let Some(first_pile_id) = first_pile_id {
+            self.players.get_mut(&next_player_id).unwrap().pending_packs.push_back(first_pile_id);
+        }
+        Ok(())
+    }
+
+    pub fn next_player_from(&self, player_id: PlayerId) -> DraftResult<PlayerId> {
         let player_turn_idx = self.turn_order.iter().position(|x| x == &player_id);
         match player_turn_idx {
             Some(idx) => {
@@ -486,7 +1691,7 @@ impl DraftState {
                 let &next_player_id = self.turn_order.get(next_player_idx).unwrap();
                 Ok(next_player_id)
             }
-            None => Err(io::Error::new(io::ErrorKind::NotFound, "Couldn't find player"))
+            None => Err(DraftError::PlayerNotFound)
         }
     }
 
@@ -499,8 +1704,7 @@ impl DraftState {
     }
 
     pub fn get_pack_size(&self) -> usize {
-        let (_, pack_size) = get_rounds_and_pack_sizes(self.players.len());
-        pack_size
+        self.pack_size
     }
 
     pub fn draft_is_done(&self) -> bool {
@@ -513,11 +1717,469 @@ impl DraftState {
             .flatten()
     }
 
-    fn set_initial_round_packs(&mut self) -> io::Result<()> {
-        let pack_ids: Vec<PackId> = self.packs_by_round.get(self.current_round_idx).unwrap().keys().cloned().collect();
-        for (i, player_state) in self.players.values_mut().into_iter().enumerate() {
-            player_state.pending_packs.push_back(pack_ids.get(i).unwrap().clone());
+    fn set_initial_round_packs(&mut self) -> DraftResult<()> {
+        // Packs are handed out in turn order, so that a draft can be restored from its turn order and packs
+        let mut pack_ids: Vec<PackId> = self.packs_by_round.get(self.current_round_idx).unwrap().keys().cloned().collect();
+        pack_ids.sort();
+        if self.format == DraftFormat::Winston {
+            // Each pile starts with a card from the stack, and the first player starts at the first pile
+            for pile_id in 1..=WINSTON_PILES as PackId {
+                let pile = self.draw_from_stack().into_iter().collect();
+                self.packs_by_round[0].insert(pile_id, pile);
+            }
+            self.players.get_mut(&self.turn_order[0]).unwrap().pending_packs.push_back(1);
+            return Ok(());
+        }
+        if matches!(self.format, DraftFormat::Rochester | DraftFormat::Grid) {
+            // Each pack starts a new snake, or each grid a new go round the table, with the first pick one player
+            // further along than the last
+            let first_player = self.turn_order[self.current_round_idx % self.turn_order.len()];
+            self.draft_direction = true;
+            if self.format == DraftFormat::Grid {
+                let grid_pack = &self.packs_by_round[self.current_round_idx][&pack_ids[0]];
+                self.grid = grid_pack.iter().map(|&item_id| Some(item_id)).collect();
+            }
+            self.players.get_mut(&first_player).unwrap().pending_packs.extend(pack_ids);
+            return Ok(());
+        }
+        // A snake draft's pool starts with the first player
+        for (player_id, pack_id) in self.turn_order.iter().zip(pack_ids) {
+            self.players.get_mut(player_id).unwrap().pending_packs.push_back(pack_id);
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::draft_database::DraftDb;
+    use crate::test_fixtures::{allocated_items, make_lobby, start_draft, start_lobby, PLAYERS};
+
+    fn first_item(pack: &PackContents, _: &PlayerState, _: &mut StdRng) -> DraftItemId {
+        pack[0]
+    }
+
+    /// Runs a booster draft from the all_stars set, dealt the way the lobby manager deals it, where every pick is
+    /// forced and chosen at random. Returns the packs dealt and what each player ended up with.
+    fn run_seeded_draft(seed: u64) -> (Vec<PackContents>, Vec<Vec<DraftItemId>>) {
+        let db = DraftDb::from_folder(concat!(env!("CARGO_MANIFEST_DIR"), "/data")).unwrap();
+        let draft_set = db.get_set(&"all_stars".to_string()).unwrap();
+        let mut lobby = make_lobby(LobbyConfig { capacity: PLAYERS.len(), ..LobbyConfig::default() }, seed, &PLAYERS);
+        let config = lobby.get_config().clone();
+        let items = draft_set.get_item_list(lobby.get_rng(), &config);
+        let packs = start_lobby(&mut lobby, &items);
+
+        let mut random_pick = |pack: &PackContents, _: &PlayerState, rng: &mut StdRng| *pack.choose(rng).unwrap();
+        let mut deadline = Some(DraftDeadline { round_number: 0, pick_number: 0, deadline: std::time::Instant::now() });
+        while let Some(DraftDeadline { round_number, pick_number, .. }) = deadline {
+            deadline = lobby.enforce_deadline(round_number, pick_number, &mut random_pick).unwrap().0;
+        }
+        assert!(lobby.draft_is_finished());
+        (packs, PLAYERS.iter().map(|&player_id| allocated_items(&lobby, player_id)).collect())
+    }
+
+    #[test]
+    fn same_seed_deals_and_drafts_the_same() {
+        let (packs, picks) = run_seeded_draft(1234);
+        assert_eq!(packs.len(), 6);
+        assert!(picks.iter().all(|player_picks| player_picks.len() == 12));
+        assert_eq!(run_seeded_draft(1234), (packs.clone(), picks));
+        assert_ne!(run_seeded_draft(4321).0, packs);
+    }
+
+    /// One item of tier S, from items 1 to 3, and two of tier A, from items 4 to 9, in every pack
+    fn collation() -> Vec<CollationRule> {
+        vec![
+            CollationRule { tier: "S".to_string(), count: 1, items: (1..=3).collect() },
+            CollationRule { tier: "A".to_string(), count: 2, items: (4..=9).collect() },
+        ]
+    }
+
+    #[test]
+    fn collated_packs_follow_every_rule() {
+        let items: Vec<DraftItemId> = (1..=20).collect();
+        for seed in 0..20 {
+            let packs = make_collated_packs(3, 5, &items, &collation(), &mut StdRng::seed_from_u64(seed)).unwrap();
+            assert_eq!(packs.len(), 3);
+            for pack in &packs {
+                assert_eq!(pack.len(), 5);
+                assert_eq!(pack.iter().filter(|id| (1..=3).contains(*id)).count(), 1);
+                assert_eq!(pack.iter().filter(|id| (4..=9).contains(*id)).count(), 2);
+            }
+            let dealt: HashSet<&DraftItemId> = packs.iter().flatten().collect();
+            assert_eq!(dealt.len(), 15);
+        }
+    }
+
+    #[test]
+    fn unfillable_collations_fail_to_deal() {
+        let items: Vec<DraftItemId> = (1..=40).collect();
+        let mut rng = StdRng::seed_from_u64(1);
+        // Four packs need four tier S items, and there are only three
+        assert!(matches!(make_collated_packs(4, 5, &items, &collation(), &mut rng), Err(DraftError::NotEnoughItems(_))));
+        assert!(matches!(make_collated_packs(3, 2, &items, &collation(), &mut rng), Err(DraftError::InvalidConfig(_))));
+
+        let mut lobby = make_lobby(LobbyConfig { capacity: 4, num_rounds: Some(1), pack_size: Some(5), ..LobbyConfig::default() }, 1, &[11, 22, 33, 44]);
+        assert!(matches!(lobby.deal_packs(&items, &collation()), Err(DraftError::NotEnoughItems(_))));
+        assert!(!lobby.draft_has_started());
+    }
+
+    #[test]
+    fn drafts_in_join_order() {
+        let items = (1..=100).collect();
+        let mut lobby = make_lobby(LobbyConfig { format: DraftFormat::Snake, capacity: 3, ..LobbyConfig::default() }, 1, &[33, 11, 22]);
+        start_lobby(&mut lobby, &items);
+        assert_eq!(lobby.get_current_player(), Some(33));
+        assert_eq!(lobby.get_draft_order(), vec!["Player 33", "Player 11", "Player 22"]);
+    }
+
+    #[test]
+    fn pick_queues_set_in_the_lobby_carry_into_the_draft() {
+        let items = (1..=100).collect();
+        let mut lobby = make_lobby(LobbyConfig { capacity: 3, ..LobbyConfig::default() }, 1, &PLAYERS);
+        let queue = PickQueue { preferences: vec![PickPreference::Species("Garchomp".to_string())], pick_immediately: true };
+        lobby.set_pick_queue(11, queue).unwrap();
+        assert_eq!(lobby.set_pick_queue(44, PickQueue::default()), Err(DraftError::PlayerNotFound));
+        assert_eq!(lobby.get_pick_queue(&11).unwrap().preferences.len(), 1);
+        assert!(lobby.get_pick_queue(&22).unwrap().preferences.is_empty());
+        assert!(lobby.get_pick_queue(&44).is_none());
+
+        start_lobby(&mut lobby, &items);
+        let pick_queue = &lobby.get_player_draft_state(&11).unwrap().pick_queue;
+        assert!(pick_queue.pick_immediately);
+        assert!(matches!(pick_queue.preferences.as_slice(), [PickPreference::Species(species)] if species == "Garchomp"));
+        assert!(lobby.get_player_draft_state(&22).unwrap().pick_queue.preferences.is_empty());
+    }
+
+    fn pick_first_in_pool(lobby: &mut DraftLobby, player_id: PlayerId) -> DraftItemId {
+        let item_id = lobby.get_public_pool().unwrap()[0];
+        lobby.make_pick(player_id, item_id).unwrap();
+        item_id
+    }
+
+    #[test]
+    fn snake_turns_reverse_at_each_end() {
+        let mut lobby = start_draft(LobbyConfig { format: DraftFormat::Snake, ..LobbyConfig::default() }, &PLAYERS);
+        let mut turns = vec![];
+        for _ in 0..10 {
+            let player_id = lobby.get_current_player().unwrap();
+            turns.push(player_id);
+            pick_first_in_pool(&mut lobby, player_id);
+        }
+        assert_eq!(turns, vec![11, 22, 33, 33, 22, 11, 11, 22, 33, 33]);
+        assert_eq!(lobby.get_draft_order(), vec!["Player 33", "Player 22", "Player 11"]);
+    }
+
+    #[test]
+    fn snake_rejects_picks_out_of_turn() {
+        let mut lobby = start_draft(LobbyConfig { format: DraftFormat::Snake, ..LobbyConfig::default() }, &PLAYERS);
+        let item_id = lobby.get_public_pool().unwrap()[0];
+        assert_eq!(lobby.make_pick(22, item_id).unwrap_err(), DraftError::NotYourTurn);
+        assert_eq!(lobby.make_pick(11, 1000).unwrap_err(), DraftError::ItemNotInPack);
+        assert_eq!(lobby.make_pick(44, item_id).unwrap_err(), DraftError::PlayerNotFound);
+        pick_first_in_pool(&mut lobby, 11);
+        assert_eq!(lobby.make_pick(11, lobby.get_public_pool().unwrap()[0]).unwrap_err(), DraftError::NotYourTurn);
+    }
+
+    #[test]
+    fn snake_deadlines_only_force_the_current_turn() {
+        let mut lobby = start_draft(LobbyConfig { format: DraftFormat::Snake, ..LobbyConfig::default() }, &PLAYERS);
+        pick_first_in_pool(&mut lobby, 11);
+        // The first turn's deadline is stale once 11 has picked
+        let (deadline, forced_picks) = lobby.enforce_deadline(0, 0, &mut first_item).unwrap();
+        assert!(deadline.is_none());
+        assert!(forced_picks.is_empty());
+
+        let expected_pick = lobby.get_public_pool().unwrap()[0];
+        let (deadline, forced_picks) = lobby.enforce_deadline(0, 1, &mut first_item).unwrap();
+        assert_eq!(forced_picks, vec![(22, expected_pick)]);
+        assert_eq!(deadline.unwrap().pick_number, 2);
+        assert_eq!(lobby.get_current_player(), Some(33));
+    }
+
+    #[test]
+    fn snake_drafts_the_whole_pool() {
+        let mut lobby = start_draft(LobbyConfig { format: DraftFormat::Snake, ..LobbyConfig::default() }, &PLAYERS);
+        // Three players get two rounds of six items each
+        while let Some(player_id) = lobby.get_current_player() {
+            pick_first_in_pool(&mut lobby, player_id);
+        }
+        assert!(lobby.draft_is_finished());
+        assert!(lobby.get_public_pool().unwrap().is_empty());
+        for player_id in PLAYERS {
+            assert_eq!(allocated_items(&lobby, player_id).len(), 12);
+        }
+    }
+
+    /// Three players drafting three packs of six, opened one at a time
+    fn start_rochester() -> DraftLobby {
+        start_draft(LobbyConfig { format: DraftFormat::Rochester, num_rounds: Some(1), pack_size: Some(6), ..LobbyConfig::default() }, &PLAYERS)
+    }
+
+    /// Picks the first item of the open pack until it's empty, returning who picked in order
+    fn pick_whole_pack(lobby: &mut DraftLobby) -> Vec<PlayerId> {
+        let mut turns = vec![];
+        while let Some(player_id) = lobby.get_current_player() {
+            turns.push(player_id);
+            pick_first_in_pool(lobby, player_id);
+            if lobby.get_public_pool().unwrap().len() == 6 {
+                break;
+            }
+        }
+        turns
+    }
+
+    #[test]
+    fn rochester_packs_are_picked_in_snake_order() {
+        let mut lobby = start_rochester();
+        let pack = lobby.get_public_pool().unwrap().clone();
+        assert_eq!(pack.len(), 6);
+        // Everyone sees the open pack, but only the player whose turn it is can pick from it
+        assert_eq!(lobby.get_current_pack_contents_for_player(&11), Some(&pack));
+        assert_eq!(lobby.get_current_pack_contents_for_player(&22), None);
+        assert_eq!(lobby.make_pick(22, pack[0]).unwrap_err(), DraftError::NotYourTurn);
+        assert_eq!(pick_whole_pack(&mut lobby), vec![11, 22, 33, 33, 22, 11]);
+        assert_eq!(allocated_items(&lobby, 11), vec![pack[0], pack[5]]);
+    }
+
+    #[test]
+    fn rochester_starts_each_pack_one_player_along() {
+        let mut lobby = start_rochester();
+        let first_pack = lobby.get_public_pool().unwrap().clone();
+        pick_whole_pack(&mut lobby);
+        assert!(lobby.get_public_pool().unwrap().iter().all(|item_id| !first_pack.contains(item_id)));
+        assert_eq!(pick_whole_pack(&mut lobby), vec![22, 33, 11, 11, 33, 22]);
+        assert_eq!(pick_whole_pack(&mut lobby), vec![33, 11, 22, 22, 11, 33]);
+        assert!(lobby.draft_is_finished());
+        for player_id in PLAYERS {
+            assert_eq!(allocated_items(&lobby, player_id).len(), 6);
+        }
+    }
+
+    /// Two players drafting three items each, from a budget of ten
+    fn start_auction() -> DraftLobby {
+        let config = LobbyConfig { format: DraftFormat::Auction, num_rounds: Some(1), pack_size: Some(3), auction_budget: 10, ..LobbyConfig::default() };
+        start_draft(config, &PLAYERS[..2])
+    }
+
+    fn budget(lobby: &DraftLobby, player_id: PlayerId) -> u32 {
+        lobby.get_player_draft_state(&player_id).unwrap().budget
+    }
+
+    #[test]
+    fn auction_bids_keep_a_point_for_each_open_slot() {
+        let mut lobby = start_auction();
+        let item_id = lobby.get_public_pool().unwrap()[0];
+        assert_eq!(lobby.nominate(22, item_id).unwrap_err(), DraftError::NotYourTurn);
+        lobby.nominate(11, item_id).unwrap();
+        assert_eq!(lobby.get_current_bid().unwrap().amount, 1);
+
+        // 22 has three open slots, so keeps two points back
+        assert_eq!(lobby.get_max_bid_for_player(&22), Some(8));
+        assert_eq!(lobby.get_max_bid_for_player(&11), None);
+        assert!(matches!(lobby.bid(22, 9).unwrap_err(), DraftError::InvalidMove(_)));
+        assert!(matches!(lobby.bid(11, 2).unwrap_err(), DraftError::InvalidMove(_)));
+        lobby.bid(22, 8).unwrap();
+        assert!(matches!(lobby.bid(11, 8).unwrap_err(), DraftError::InvalidMove(_)));
+        assert_eq!(lobby.close_auction().unwrap(), (22, item_id, 8));
+        assert_eq!(budget(&lobby, 22), 2);
+        assert_eq!(allocated_items(&lobby, 22), vec![item_id]);
+
+        // The nomination passes on, and 22 can only afford one point with two slots to fill
+        assert_eq!(lobby.get_current_player(), Some(22));
+        let item_id = lobby.get_public_pool().unwrap()[0];
+        lobby.nominate(22, item_id).unwrap();
+        lobby.bid(11, 2).unwrap();
+        assert_eq!(lobby.get_max_bid_for_player(&22), None);
+        assert!(matches!(lobby.bid(22, 3).unwrap_err(), DraftError::InvalidMove(_)));
+        assert_eq!(lobby.close_auction().unwrap(), (11, item_id, 2));
+    }
+
+    #[test]
+    fn auction_nominations_skip_full_teams() {
+        let mut lobby = start_auction();
+        let sell = |lobby: &mut DraftLobby, nominated_by: PlayerId, winning_bid: Option<(PlayerId, u32)>| {
+            assert_eq!(lobby.get_current_player(), Some(nominated_by));
+            let item_id = lobby.get_public_pool().unwrap()[0];
+            lobby.nominate(nominated_by, item_id).unwrap();
+            if let Some((player_id, amount)) = winning_bid {
+                lobby.bid(player_id, amount).unwrap();
+            }
+            lobby.close_auction().unwrap()
+        };
+        sell(&mut lobby, 11, None);
+        sell(&mut lobby, 22, Some((11, 2)));
+        sell(&mut lobby, 11, None);
+        assert_eq!(allocated_items(&lobby, 11).len(), 3);
+
+        // 11's team is full, so it can't bid, and 22 nominates every item from now on
+        let item_id = lobby.get_public_pool().unwrap()[0];
+        lobby.nominate(22, item_id).unwrap();
+        assert_eq!(lobby.get_max_bid_for_player(&11), None);
+        assert!(matches!(lobby.bid(11, 2).unwrap_err(), DraftError::InvalidMove(_)));
+        lobby.close_auction().unwrap();
+        sell(&mut lobby, 22, None);
+        sell(&mut lobby, 22, None);
+        assert!(lobby.draft_is_finished());
+        assert_eq!(allocated_items(&lobby, 22).len(), 3);
+    }
+
+    #[test]
+    fn auction_deadlines_fill_every_team() {
+        let mut lobby = start_auction();
+        let mut turn_idx = Some(0);
+        let mut timeouts = vec![];
+        while let Some(idx) = turn_idx {
+            let (deadline, timeout) = lobby.enforce_auction_deadline(idx).unwrap();
+            timeouts.extend(timeout);
+            turn_idx = deadline.map(|deadline| deadline.pick_number);
+        }
+        assert!(lobby.draft_is_finished());
+        // Nobody bids, so every item is nominated and then sold for a point
+        assert_eq!(timeouts.len(), 12);
+        assert!(matches!(timeouts[0], AuctionTimeout::Nominated { player_id: 11, .. }));
+        assert!(matches!(timeouts[1], AuctionTimeout::Sold { player_id: 11, price: 1, .. }));
+        for player_id in [11, 22] {
+            assert_eq!(allocated_items(&lobby, player_id).len(), 3);
+            assert_eq!(budget(&lobby, player_id), 7);
+        }
+        // A stale deadline does nothing
+        assert!(matches!(lobby.enforce_auction_deadline(0).unwrap(), (None, None)));
+    }
+
+    /// Two players drafting from six cards: three start in the piles, and three in the stack
+    fn start_winston() -> DraftLobby {
+        let config = LobbyConfig { format: DraftFormat::Winston, num_rounds: Some(1), pack_size: Some(3), ..LobbyConfig::default() };
+        start_draft(config, &PLAYERS[..2])
+    }
+
+    fn pile_sizes(lobby: &DraftLobby) -> (usize, Vec<usize>) {
+        let piles = lobby.get_winston_piles(&11).unwrap();
+        (piles.stack_size, piles.pile_sizes)
+    }
+
+    #[test]
+    fn winston_taking_a_pile_refills_it_and_passes_the_turn() {
+        let mut lobby = start_winston();
+        assert_eq!(pile_sizes(&lobby), (3, vec![1, 1, 1]));
+        assert_eq!(lobby.take_pile(22).unwrap_err(), DraftError::NotYourTurn);
+        lobby.take_pile(11).unwrap();
+        assert_eq!(allocated_items(&lobby, 11).len(), 1);
+        assert_eq!(pile_sizes(&lobby), (2, vec![1, 1, 1]));
+        assert_eq!(lobby.get_current_player(), Some(22));
+        assert_eq!(lobby.get_winston_piles(&22).unwrap().current_pile, Some(1));
+    }
+
+    #[test]
+    fn winston_passing_adds_to_the_pile_and_moves_on() {
+        let mut lobby = start_winston();
+        lobby.pass_pile(11).unwrap();
+        assert_eq!(pile_sizes(&lobby), (2, vec![2, 1, 1]));
+        assert_eq!(lobby.get_winston_piles(&11).unwrap().current_pile, Some(2));
+        lobby.pass_pile(11).unwrap();
+        assert_eq!(pile_sizes(&lobby), (1, vec![2, 2, 1]));
+
+        // Passing on the last pile would need a card to add to it and another to take, but the stack only has one
+        assert!(matches!(lobby.pass_pile(11).unwrap_err(), DraftError::InvalidMove(_)));
+        lobby.take_pile(11).unwrap();
+        assert_eq!(pile_sizes(&lobby), (0, vec![2, 2, 1]));
+        assert_eq!(lobby.get_current_player(), Some(22));
+    }
+
+    #[test]
+    fn winston_passing_the_last_pile_takes_from_the_stack() {
+        let config = LobbyConfig { format: DraftFormat::Winston, num_rounds: Some(1), pack_size: Some(4), ..LobbyConfig::default() };
+        let mut lobby = start_draft(config, &PLAYERS[..2]);
+        assert_eq!(pile_sizes(&lobby), (5, vec![1, 1, 1]));
+        for _ in 0..3 {
+            lobby.pass_pile(11).unwrap();
+        }
+        // Every pile got a card from the stack, and 11 took the next one unseen
+        assert_eq!(pile_sizes(&lobby), (1, vec![2, 2, 2]));
+        assert_eq!(allocated_items(&lobby, 11).len(), 1);
+        assert_eq!(lobby.get_current_player(), Some(22));
+    }
+
+    #[test]
+    fn winston_deadlines_take_piles_until_every_card_is_drafted() {
+        let mut lobby = start_winston();
+        let mut turn_idx = Some(0);
+        while let Some(idx) = turn_idx {
+            let (deadline, player_id) = lobby.enforce_winston_deadline(idx).unwrap();
+            assert!(player_id.is_some());
+            turn_idx = deadline.map(|deadline| deadline.pick_number);
+        }
+        assert!(lobby.draft_is_finished());
+        assert_eq!(pile_sizes(&lobby), (0, vec![0, 0, 0]));
+        assert_eq!(allocated_items(&lobby, 11).len() + allocated_items(&lobby, 22).len(), 6);
+    }
+
+    /// One grid for each player
+    fn start_grid(players: &[PlayerId]) -> DraftLobby {
+        start_draft(LobbyConfig { format: DraftFormat::Grid, num_rounds: Some(1), ..LobbyConfig::default() }, players)
+    }
+
+    fn grid_items_left(lobby: &DraftLobby) -> usize {
+        lobby.get_grid().unwrap().iter().flatten().count()
+    }
+
+    #[test]
+    fn grid_lines_take_what_is_left_of_them() {
+        let mut lobby = start_grid(&PLAYERS);
+        let grid = lobby.get_grid().unwrap().clone();
+        assert_eq!(lobby.take_line(22, GridLine::Row(0)).unwrap_err(), DraftError::NotYourTurn);
+        assert!(matches!(lobby.take_line(11, GridLine::Row(3)).unwrap_err(), DraftError::InvalidMove(_)));
+        lobby.take_line(11, GridLine::Row(0)).unwrap();
+        assert_eq!(allocated_items(&lobby, 11), vec![grid[0].unwrap(), grid[1].unwrap(), grid[2].unwrap()]);
+
+        assert!(matches!(lobby.take_line(22, GridLine::Row(0)).unwrap_err(), DraftError::InvalidMove(_)));
+        lobby.take_line(22, GridLine::Column(0)).unwrap();
+        assert_eq!(allocated_items(&lobby, 22), vec![grid[3].unwrap(), grid[6].unwrap()]);
+        assert_eq!(grid_items_left(&lobby), 4);
+        assert_eq!(lobby.get_current_player(), Some(33));
+    }
+
+    #[test]
+    fn grid_is_discarded_once_everyone_has_taken_a_line() {
+        let mut lobby = start_grid(&PLAYERS);
+        let first_grid = lobby.get_grid().unwrap().clone();
+        lobby.take_line(11, GridLine::Row(0)).unwrap();
+        lobby.take_line(22, GridLine::Column(0)).unwrap();
+        lobby.take_line(33, GridLine::Column(1)).unwrap();
+
+        // Two items of the first grid are left over, and the next grid starts one player along
+        assert_eq!(allocated_items(&lobby, 33).len(), 2);
+        let second_grid = lobby.get_grid().unwrap();
+        assert_eq!(grid_items_left(&lobby), 9);
+        assert!(second_grid.iter().all(|cell| !first_grid.contains(cell)));
+        assert_eq!(lobby.get_current_player(), Some(22));
+        assert_eq!(lobby.get_draft_progress_for_player(&22).unwrap().0, 2);
+    }
+
+    #[test]
+    fn grid_deadlines_take_the_fullest_line() {
+        let mut lobby = start_grid(&PLAYERS[..2]);
+        lobby.take_line(11, GridLine::Row(0)).unwrap();
+        // Stale deadlines do nothing
+        assert!(matches!(lobby.enforce_grid_deadline(0).unwrap(), (None, None)));
+
+        // Columns only have two items left, so a row is taken
+        let (deadline, timeout) = lobby.enforce_grid_deadline(1).unwrap();
+        let timeout = timeout.unwrap();
+        assert_eq!(timeout.player_id, 22);
+        assert!(matches!(timeout.line, GridLine::Row(1) | GridLine::Row(2)));
+        assert_eq!(allocated_items(&lobby, 22).len(), 3);
+
+        // With two players the grid is done, and 22 starts the second and last one
+        assert_eq!(lobby.get_current_player(), Some(22));
+        let mut turn_idx = deadline.map(|deadline| deadline.pick_number);
+        while let Some(idx) = turn_idx {
+            turn_idx = lobby.enforce_grid_deadline(idx).unwrap().0.map(|deadline| deadline.pick_number);
+        }
+        assert!(lobby.draft_is_finished());
+        assert_eq!(allocated_items(&lobby, 11).len(), 6);
+        assert_eq!(allocated_items(&lobby, 22).len(), 6);
+    }
+}
@@ -0,0 +1,178 @@
+use std::fs;
+use std::io;
+use std::io::Write;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::bots::BotStrategy;
+use crate::draft_engine::{DraftItemId, GridLine, LobbyConfig, PackContents, PickQueue, PlayerId};
+use crate::lobby_manager::DraftLobbyId;
+
+/// A change to a lobby's state. Replaying every event in order rebuilds the lobbies.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "event")]
+pub enum LobbyEvent {
+    LobbyCreated { lobby_id: DraftLobbyId, set_name: String, seed: u64, config: LobbyConfig },
+    PlayerJoined { lobby_id: DraftLobbyId, player_id: PlayerId, player_name: String },
+    BotAdded { lobby_id: DraftLobbyId, player_id: PlayerId, player_name: String, strategy: BotStrategy },
+    LobbyStarted { lobby_id: DraftLobbyId, turn_order: Vec<PlayerId>, packs: Vec<PackContents> },
+    PickMade { lobby_id: DraftLobbyId, player_id: PlayerId, pick: DraftItemId },
+    PickForced { lobby_id: DraftLobbyId, player_id: PlayerId, pick: DraftItemId },
+    ItemNominated { lobby_id: DraftLobbyId, player_id: PlayerId, pick: DraftItemId },
+    BidPlaced { lobby_id: DraftLobbyId, player_id: PlayerId, amount: u32 },
+    ItemSold { lobby_id: DraftLobbyId, player_id: PlayerId, pick: DraftItemId, price: u32 },
+    PileTaken { lobby_id: DraftLobbyId, player_id: PlayerId },
+    PilePassed { lobby_id: DraftLobbyId, player_id: PlayerId },
+    LineTaken { lobby_id: DraftLobbyId, player_id: PlayerId, line: GridLine },
+    PickQueueSet { lobby_id: DraftLobbyId, player_id: PlayerId, queue: PickQueue },
+}
+
+impl LobbyEvent {
+    pub fn lobby_id(&self) -> DraftLobbyId {
+        match self {
+            LobbyEvent::LobbyCreated { lobby_id, .. } => *lobby_id,
+            LobbyEvent::PlayerJoined { lobby_id, .. } => *lobby_id,
+            LobbyEvent::BotAdded { lobby_id, .. } => *lobby_id,
+            LobbyEvent::LobbyStarted { lobby_id, .. } => *lobby_id,
+            LobbyEvent::PickMade { lobby_id, .. } => *lobby_id,
+            LobbyEvent::PickForced { lobby_id, .. } => *lobby_id,
+            LobbyEvent::ItemNominated { lobby_id, .. } => *lobby_id,
+            LobbyEvent::BidPlaced { lobby_id, .. } => *lobby_id,
+            LobbyEvent::ItemSold { lobby_id, .. } => *lobby_id,
+            LobbyEvent::PileTaken { lobby_id, .. } => *lobby_id,
+            LobbyEvent::PilePassed { lobby_id, .. } => *lobby_id,
+            LobbyEvent::LineTaken { lobby_id, .. } => *lobby_id,
+            LobbyEvent::PickQueueSet { lobby_id, .. } => *lobby_id,
+        }
+    }
+}
+
+/// An append-only log of lobby events, one JSON object per line
+pub struct EventLog {
+    file: fs::File,
+}
+
+impl EventLog {
+    /// Opens the log at `path` for appending, creating it if needed, and returns the events already in it
+    pub fn open(path: &str) -> io::Result<(EventLog, Vec<LobbyEvent>)> {
+        let contents = match fs::read(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => vec![],
+            Err(e) => return Err(e),
+        };
+        let (events, complete_len) = read_events(&contents)?;
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+        // Cut off a truncated last line, and finish a complete one that lost its newline, so the next event starts on
+        // a line of its own
+        if complete_len < contents.len() {
+            file.set_len(complete_len as u64)?;
+        }
+        if complete_len > 0 && contents[complete_len - 1] != b'\n' {
+            file.write_all(b"\n")?;
+        }
+        Ok((EventLog { file }, events))
+    }
+
+    /// Writes an event and syncs it to disk, so that it survives a crash straight afterwards
+    pub fn append(&mut self, event: &LobbyEvent) -> io::Result<()> {
+        let mut line = serde_json::to_string(event)?;
+        line.push('\n');
+        self.file.write_all(line.as_bytes())?;
+        self.file.sync_data()
+    }
+}
+
+/// Reads the events in a log, along with the length of the log up to the end of the last complete event
+fn read_events(contents: &[u8]) -> io::Result<(Vec<LobbyEvent>, usize)> {
+    let mut events = vec![];
+    let mut complete_len = 0;
+    let lines: Vec<&[u8]> = contents.split_inclusive(|&b| b == b'\n').collect();
+    for (idx, line) in lines.iter().enumerate() {
+        if !line.trim_ascii().is_empty() {
+            match serde_json::from_slice(line) {
+                Ok(event) => events.push(event),
+                // A crash part way through a write leaves a truncated last line, which is safe to drop
+                Err(e) if idx + 1 == lines.len() => {
+                    log::warn!("Ignoring incomplete last event in the event log: {e}");
+                    break;
+                }
+                Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("event log line {}: {e}", idx + 1))),
+            }
+        }
+        complete_len += line.len();
+    }
+    Ok((events, complete_len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_log(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("pkmn-draft-{}-{name}.log", std::process::id()));
+        fs::remove_file(&path).ok();
+        path.to_str().unwrap().to_string()
+    }
+
+    fn pile_taken(player_id: PlayerId) -> LobbyEvent {
+        LobbyEvent::PileTaken { lobby_id: 1, player_id }
+    }
+
+    fn player_ids(events: &[LobbyEvent]) -> Vec<PlayerId> {
+        events.iter().map(|event| match event {
+            LobbyEvent::PileTaken { player_id, .. } => *player_id,
+            other => panic!("Unexpected event {other:?}"),
+        }).collect()
+    }
+
+    #[test]
+    fn replays_appended_events() {
+        let path = temp_log("replay");
+        let (mut log, events) = EventLog::open(&path).unwrap();
+        assert!(events.is_empty());
+        log.append(&pile_taken(1)).unwrap();
+        log.append(&pile_taken(2)).unwrap();
+        let (_, events) = EventLog::open(&path).unwrap();
+        assert_eq!(player_ids(&events), vec![1, 2]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn recovers_from_a_truncated_last_line() {
+        let path = temp_log("truncated");
+        let (mut log, _) = EventLog::open(&path).unwrap();
+        log.append(&pile_taken(1)).unwrap();
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"event":"PileTaken","lobby_id":1,"pla"#).unwrap();
+
+        // The partial event is dropped, and the next one goes on a line of its own
+        let (mut log, events) = EventLog::open(&path).unwrap();
+        assert_eq!(player_ids(&events), vec![1]);
+        log.append(&pile_taken(2)).unwrap();
+        let (_, events) = EventLog::open(&path).unwrap();
+        assert_eq!(player_ids(&events), vec![1, 2]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn keeps_a_last_event_missing_its_newline() {
+        let path = temp_log("newline");
+        fs::write(&path, serde_json::to_string(&pile_taken(1)).unwrap()).unwrap();
+        let (mut log, events) = EventLog::open(&path).unwrap();
+        assert_eq!(player_ids(&events), vec![1]);
+        log.append(&pile_taken(2)).unwrap();
+        let (_, events) = EventLog::open(&path).unwrap();
+        assert_eq!(player_ids(&events), vec![1, 2]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rejects_corruption_before_the_last_line() {
+        let path = temp_log("corrupt");
+        let event = serde_json::to_string(&pile_taken(1)).unwrap();
+        fs::write(&path, format!("{{\"event\"\n{event}\n")).unwrap();
+        let error = EventLog::open(&path).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,23 +1,55 @@
-use std::collections::HashMap;
-use std::io;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 use rand::{RngCore};
+use rand::rngs::StdRng;
+use serde_derive::Serialize;
 
-use crate::draft_database::DraftDb;
+use crate::bots;
+use crate::bots::{BotStrategy, HeuristicPick, PickStrategy};
+use crate::draft_database::{DraftDb, DraftItemDetails, DraftSet, SetItemSummary, SetSummary};
 use crate::draft_engine;
-use crate::draft_engine::{DraftDeadline, DraftItemId, GameState, PlayerId};
+use crate::draft_engine::{AuctionTimeout, DraftDeadline, DraftError, DraftEvent, DraftFormat, DraftItemId, DraftResult, GameState, GridLine, GridTimeout, LobbyOptions, PickPreference, PickQueue, PlayerId, PlayerState, WinstonPiles};
+use crate::event_log::{EventLog, LobbyEvent};
 
 pub type DraftLobbyId = u64;
 
-#[derive(Debug)]
+/// The item up for auction, as shown to a player
+#[derive(Serialize, Debug)]
+pub struct CurrentBid {
+    pub template: String,
+    pub stats: String,
+    pub high_bidder: String,
+    pub amount: u32,
+    /// The most this player can bid, if they can bid at all
+    pub max_bid: Option<u32>,
+}
+
+#[derive(Serialize, Debug)]
 pub struct LobbyStateForPlayer {
     pub lobby_id: DraftLobbyId,
     pub player_id: PlayerId,
     pub joining_players: Vec<String>,
     pub open_slots: Vec<String>,
+    /// Whether open slots can be filled with bots, which only play some formats
+    pub can_add_bots: bool,
     pub pending_picks: Vec<(DraftItemId, String, String)>,
+    pub public_pool: Vec<(DraftItemId, String, String)>,
+    /// The player's pick queue, if the draft is one where they can queue picks
+    pub pick_queue: Option<PickQueue>,
+    /// What each entry of the pick queue asks for, for showing the queue
+    pub pick_queue_names: Vec<String>,
+    /// In turn-based drafts, the name of the player whose turn it is
+    pub current_turn: Option<String>,
+    pub current_bid: Option<CurrentBid>,
+    pub winston_piles: Option<WinstonPiles>,
+    /// The current grid of a grid draft, row by row, with `None` where lines have been taken
+    pub grid: Vec<Option<(String, String)>>,
+    pub can_take_line: bool,
+    pub budgets: Vec<(String, u32)>,
     pub allocated_picks: Vec<(String, String)>,
     pub game_state: GameState,
+    pub seed: u64,
     pub draft_is_finished: bool,
     pub time_to_pick_s: Option<u64>,
     pub draft_order: Vec<String>,
@@ -25,24 +57,97 @@ pub struct LobbyStateForPlayer {
     pub raw_picks: Vec<String>,
 }
 
+/// Where a player is in a draft
+#[derive(Serialize, Debug)]
+pub struct DraftProgress {
+    pub round: usize,
+    pub total_rounds: usize,
+    pub pick: usize,
+    pub pack_size: usize,
+}
+
+/// The item up for auction, as returned by `/api/draft/{lobby}/{player}`
+#[derive(Serialize, Debug)]
+pub struct CurrentBidSummary {
+    pub item: DraftItemDetails,
+    pub high_bidder: String,
+    pub amount: u32,
+    /// The most this player can bid, if they can bid at all
+    pub max_bid: Option<u32>,
+}
+
+/// A player's view of a lobby for the JSON API, with items as data rather than rendered HTML
+#[derive(Serialize, Debug)]
+pub struct LobbyStateSummary {
+    pub lobby_id: DraftLobbyId,
+    pub player_id: PlayerId,
+    pub set_name: String,
+    pub format: DraftFormat,
+    pub capacity: usize,
+    /// Everyone in the lobby, in the order they joined
+    pub players: Vec<String>,
+    pub started: bool,
+    pub finished: bool,
+    pub draft_order: Vec<String>,
+    pub current_turn: Option<String>,
+    pub pending_picks: Vec<DraftItemDetails>,
+    pub public_pool: Vec<DraftItemDetails>,
+    pub picks: Vec<DraftItemDetails>,
+    pub pick_queue: Option<PickQueue>,
+    pub current_bid: Option<CurrentBidSummary>,
+    pub budgets: Vec<(String, u32)>,
+    pub winston_piles: Option<WinstonPiles>,
+    /// The current grid of a grid draft, row by row, with `None` where lines have been taken
+    pub grid: Vec<Option<DraftItemDetails>>,
+    pub progress: Option<DraftProgress>,
+    pub time_to_pick_s: Option<u64>,
+    /// Changes whenever there's something new for the player to see, see the `poll` command
+    pub game_state: GameState,
+    pub seed: u64,
+}
+
 pub enum LobbyManagerRequest {
-    CreateLobby { set_name: String },
+    CreateLobby { set_name: String, seed: Option<u64>, options: LobbyOptions },
     JoinLobby { lobby_id: DraftLobbyId, player_name: String },
+    AddBot { lobby_id: DraftLobbyId, strategy: BotStrategy },
     StartLobby { lobby_id: DraftLobbyId },
     GetLobbyState { lobby_id: DraftLobbyId, player_id: PlayerId },
+    GetLobbySummary { lobby_id: DraftLobbyId, player_id: PlayerId },
     MakePick { lobby_id: DraftLobbyId, player_id: PlayerId, pick: DraftItemId },
+    Nominate { lobby_id: DraftLobbyId, player_id: PlayerId, pick: DraftItemId },
+    Bid { lobby_id: DraftLobbyId, player_id: PlayerId, amount: u32 },
+    TakePile { lobby_id: DraftLobbyId, player_id: PlayerId },
+    PassPile { lobby_id: DraftLobbyId, player_id: PlayerId },
+    TakeLine { lobby_id: DraftLobbyId, player_id: PlayerId, line: GridLine },
+    SetPickQueue { lobby_id: DraftLobbyId, player_id: PlayerId, queue: PickQueue },
     BlockForUpdate { lobby_id: DraftLobbyId, player_id: PlayerId, game_state: GameState },
+    StreamEvents { lobby_id: DraftLobbyId, player_id: PlayerId },
     EnforceDeadline { lobby_id: DraftLobbyId, round_number: usize, pick_number: usize },
+    ListSets,
+    GetSetItems { set_name: String },
+    ReloadSets,
 }
 
 pub enum LobbyManagerResponse {
-    LobbyErrorMsg(String),
+    LobbyError(DraftError),
     LobbyCreated(DraftLobbyId),
     LobbyJoined { lobby_id: DraftLobbyId, player_id: PlayerId },
+    BotAdded,
     LobbyStarted,
     PickMade,
-    LobbyState(LobbyStateForPlayer),
+    Nominated,
+    BidPlaced,
+    PileTaken,
+    PilePassed,
+    LineTaken,
+    PickQueueSet,
+    LobbyState(Box<LobbyStateForPlayer>),
+    LobbySummary(Box<LobbyStateSummary>),
     UpdateReady,
+    EventStream(tokio::sync::mpsc::UnboundedReceiver<DraftEvent>),
+    SetList(Vec<SetSummary>),
+    SetItems(Vec<SetItemSummary>),
+    SetsReloaded { num_sets: usize },
 }
 
 pub struct LobbyManagerTask {
@@ -50,26 +155,104 @@ pub struct LobbyManagerTask {
     pub response_channel: tokio::sync::oneshot::Sender<LobbyManagerResponse>,
 }
 
-const MAX_LOBBY_CAPACITY: usize = 6;
-
 pub struct LobbyManager {
     draft_database: DraftDb,
     active_lobbies: HashMap<DraftLobbyId, draft_engine::DraftLobby>,
+    // The version of the set each lobby was created with, which survives reloading the database
+    lobby_sets: HashMap<DraftLobbyId, Arc<DraftSet>>,
     task_queue: tokio::sync::mpsc::Receiver<LobbyManagerTask>,
     self_queue: tokio::sync::mpsc::Sender<LobbyManagerTask>,
     scheduling: timer::Timer,
+    event_log: Option<EventLog>,
 }
 
 impl LobbyManager {
     pub fn new(task_queue: tokio::sync::mpsc::Receiver<LobbyManagerTask>,
                self_queue: tokio::sync::mpsc::Sender<LobbyManagerTask>,
-               draft_database: DraftDb) -> LobbyManager {
+               draft_database: DraftDb,
+               event_log: Option<EventLog>) -> LobbyManager {
         LobbyManager {
             draft_database,
             active_lobbies: HashMap::new(),
+            lobby_sets: HashMap::new(),
             task_queue,
             self_queue,
             scheduling: timer::Timer::new(),
+            event_log,
+        }
+    }
+
+    /// Rebuilds lobbies from the events in the event log, and re-arms the deadlines of drafts in progress.
+    /// Lobbies with events that no longer apply, e.g. because their set has changed, are dropped.
+    pub fn replay_events(&mut self, events: Vec<LobbyEvent>) {
+        let mut broken_lobbies: HashSet<DraftLobbyId> = HashSet::new();
+        for event in events {
+            let lobby_id = event.lobby_id();
+            if broken_lobbies.contains(&lobby_id) {
+                continue;
+            }
+            if let Err(e) = self.replay_event(event) {
+                log::error!("Dropping lobby {lobby_id}, as it couldn't be restored: {e}");
+                self.active_lobbies.remove(&lobby_id);
+                self.lobby_sets.remove(&lobby_id);
+                broken_lobbies.insert(lobby_id);
+            }
+        }
+
+        let mut deadlines = vec![];
+        for (&lobby_id, lobby) in self.active_lobbies.iter_mut() {
+            if let Some(deadline) = lobby.resume_deadlines() {
+                deadlines.push((lobby_id, deadline));
+            }
+        }
+        for (lobby_id, deadline) in deadlines {
+            self.enqueue_deadline(lobby_id, deadline);
+        }
+        // In case the server stopped before a bot could pick
+        let lobby_ids: Vec<DraftLobbyId> = self.active_lobbies.keys().copied().collect();
+        for lobby_id in lobby_ids {
+            self.run_auto_picks(lobby_id);
+        }
+        log::info!("Restored {} lobbies from the event log", self.active_lobbies.len());
+    }
+
+    fn replay_event(&mut self, event: LobbyEvent) -> DraftResult<()> {
+        if let LobbyEvent::LobbyCreated { lobby_id, set_name, seed, config } = event {
+            let draft_set = self.draft_database.get_set(&set_name)
+                .ok_or(DraftError::UnknownSet(set_name.clone()))?;
+            self.lobby_sets.insert(lobby_id, draft_set);
+            self.active_lobbies.insert(lobby_id, draft_engine::DraftLobby::new(set_name, config, seed));
+            return Ok(());
+        }
+
+        let lobby_id = event.lobby_id();
+        let lobby = self.active_lobbies.get_mut(&lobby_id)
+            .ok_or(DraftError::LobbyNotFound)?;
+        match event {
+            LobbyEvent::PlayerJoined { player_id, player_name, .. } => lobby.add_player_with_id(player_id, player_name),
+            LobbyEvent::BotAdded { player_id, player_name, strategy, .. } => lobby.add_bot_with_id(player_id, player_name, strategy),
+            LobbyEvent::LobbyStarted { turn_order, packs, .. } => {
+                let draft_set = self.lobby_sets.get(&lobby_id).unwrap();
+                if packs.iter().flatten().any(|item_id| draft_set.get_item_by_id(item_id).is_none()) {
+                    return Err(DraftError::Internal("Packs contain items that are no longer in the set".to_string()));
+                }
+                lobby.start(turn_order, packs).map(|_| ())
+            }
+            LobbyEvent::PickMade { player_id, pick, .. } | LobbyEvent::PickForced { player_id, pick, .. } =>
+                lobby.make_pick(player_id, pick).map(|_| ()),
+            LobbyEvent::ItemNominated { player_id, pick, .. } => lobby.nominate(player_id, pick).map(|_| ()),
+            LobbyEvent::BidPlaced { player_id, amount, .. } => lobby.bid(player_id, amount).map(|_| ()),
+            LobbyEvent::ItemSold { player_id, pick, price, .. } => {
+                if lobby.close_auction()? != (player_id, pick, price) {
+                    return Err(DraftError::Internal("Auction sold to a different player than logged".to_string()));
+                }
+                Ok(())
+            }
+            LobbyEvent::PileTaken { player_id, .. } => lobby.take_pile(player_id).map(|_| ()),
+            LobbyEvent::PilePassed { player_id, .. } => lobby.pass_pile(player_id).map(|_| ()),
+            LobbyEvent::LineTaken { player_id, line, .. } => lobby.take_line(player_id, line).map(|_| ()),
+            LobbyEvent::PickQueueSet { player_id, queue, .. } => lobby.set_pick_queue(player_id, queue),
+            LobbyEvent::LobbyCreated { .. } => Ok(()),
         }
     }
 
@@ -97,7 +280,7 @@ impl LobbyManager {
         match self.active_lobbies.get_mut(&lobby_id) {
             None => {
                 log::warn!("Tried to poll a lobby that doesn't exist, returning immediately");
-                match listener.send(LobbyManagerResponse::LobbyErrorMsg("Polled a lobby that doesn't exist".to_string())) {
+                match listener.send(LobbyManagerResponse::LobbyError(DraftError::LobbyNotFound)) {
                     Ok(_) => {}
                     Err(_) => log::warn!("Receiver dropped"),
                 }
@@ -113,37 +296,74 @@ impl LobbyManager {
 
     fn process_request(&mut self, request: LobbyManagerRequest) -> LobbyManagerResponse {
         match request {
-            LobbyManagerRequest::CreateLobby {set_name} => match self.create_lobby(set_name) {
-                Some(lobby_id) => LobbyManagerResponse::LobbyCreated(lobby_id),
-                None => LobbyManagerResponse::LobbyErrorMsg("Unknown draft set".to_string())
-            },
+            LobbyManagerRequest::CreateLobby { set_name, seed, options } => self.create_lobby(set_name, seed, options),
             LobbyManagerRequest::JoinLobby { lobby_id, player_name } => self.join_lobby(lobby_id, player_name),
+            LobbyManagerRequest::AddBot { lobby_id, strategy } => self.add_bot(lobby_id, strategy),
             LobbyManagerRequest::StartLobby { lobby_id } => self.start_lobby(lobby_id),
             LobbyManagerRequest::GetLobbyState { lobby_id, player_id } => match self.get_lobby_state(lobby_id, player_id) {
-                Ok(s) => LobbyManagerResponse::LobbyState(s),
+                Ok(s) => LobbyManagerResponse::LobbyState(Box::new(s)),
                 Err(e) => {
                     log::error!("Error retrieving state {e}");
-                    LobbyManagerResponse::LobbyErrorMsg("Error fetching state".to_string())
+                    LobbyManagerResponse::LobbyError(e)
+                }
+            },
+            LobbyManagerRequest::GetLobbySummary { lobby_id, player_id } => match self.get_lobby_summary(lobby_id, player_id) {
+                Ok(s) => LobbyManagerResponse::LobbySummary(Box::new(s)),
+                Err(e) => {
+                    log::warn!("Error summarising lobby {lobby_id}: {e}");
+                    LobbyManagerResponse::LobbyError(e)
                 }
             },
             LobbyManagerRequest::MakePick { lobby_id, player_id, pick } => self.make_pick(lobby_id, player_id, pick),
-            _ => LobbyManagerResponse::LobbyErrorMsg("Not implemented".to_string())
+            LobbyManagerRequest::Nominate { lobby_id, player_id, pick } => self.nominate(lobby_id, player_id, pick),
+            LobbyManagerRequest::Bid { lobby_id, player_id, amount } => self.bid(lobby_id, player_id, amount),
+            LobbyManagerRequest::TakePile { lobby_id, player_id } => self.take_pile(lobby_id, player_id),
+            LobbyManagerRequest::PassPile { lobby_id, player_id } => self.pass_pile(lobby_id, player_id),
+            LobbyManagerRequest::TakeLine { lobby_id, player_id, line } => self.take_line(lobby_id, player_id, line),
+            LobbyManagerRequest::SetPickQueue { lobby_id, player_id, queue } => self.set_pick_queue(lobby_id, player_id, queue),
+            LobbyManagerRequest::StreamEvents { lobby_id, player_id } => self.stream_events(lobby_id, player_id),
+            LobbyManagerRequest::ListSets => LobbyManagerResponse::SetList(self.draft_database.list_sets()),
+            LobbyManagerRequest::GetSetItems { set_name } => match self.draft_database.list_set_items(&set_name) {
+                Some(items) => LobbyManagerResponse::SetItems(items),
+                None => LobbyManagerResponse::LobbyError(DraftError::UnknownSet(set_name)),
+            },
+            LobbyManagerRequest::ReloadSets => self.reload_sets(),
+            _ => LobbyManagerResponse::LobbyError(DraftError::Internal("Not implemented".to_string()))
         }
     }
 
-    fn create_lobby(&mut self, set_name: String) -> Option<DraftLobbyId> {
-        let lobby_id = self.generate_lobby_id();
-        match self.draft_database.get_set(&set_name) {
-            Some(_) => {
-                log::info!("Creating lobby {lobby_id} for set {set_name}");
-                self.active_lobbies.insert(lobby_id, draft_engine::DraftLobby::new(set_name, MAX_LOBBY_CAPACITY));
-                Some(lobby_id)
-            },
+    fn create_lobby(&mut self, set_name: String, seed: Option<u64>, options: LobbyOptions) -> LobbyManagerResponse {
+        let draft_set = match self.draft_database.get_set(&set_name) {
+            Some(draft_set) => draft_set,
             None => {
                 log::error!("Got a request for unknown draft set {set_name}");
-                None
+                return LobbyManagerResponse::LobbyError(DraftError::UnknownSet(set_name));
             }
+        };
+        let config = draft_set.get_manifest().default_lobby_config().with_options(&options);
+        if let Err(e) = config.validate() {
+            return LobbyManagerResponse::LobbyError(e);
+        }
+        if let Err(e) = draft_set.check_ban_list(&config) {
+            return LobbyManagerResponse::LobbyError(e);
+        }
+        if config.num_items_required() > draft_set.num_unique_items(&config) {
+            log::warn!("Rejected a lobby for set {set_name} that needs too many items: {config:?}");
+            return LobbyManagerResponse::LobbyError(DraftError::NotEnoughItems(format!("This set only has {} unique Pokemon, but a full lobby with these settings needs {}",
+                                                                                      draft_set.num_unique_items(&config), config.num_items_required())));
         }
+        if let Err(e) = draft_set.check_pack_collation(&config) {
+            log::warn!("Rejected a lobby for set {set_name} that can't follow the set's pack collation: {config:?}");
+            return LobbyManagerResponse::LobbyError(e);
+        }
+
+        let lobby_id = self.generate_lobby_id();
+        let seed = seed.unwrap_or_else(|| rand::thread_rng().next_u64());
+        log::info!("Creating lobby {lobby_id} for set {set_name} with seed {seed} and {config:?}");
+        self.lobby_sets.insert(lobby_id, draft_set);
+        self.active_lobbies.insert(lobby_id, draft_engine::DraftLobby::new(set_name.clone(), config.clone(), seed));
+        self.log_event(LobbyEvent::LobbyCreated { lobby_id, set_name, seed, config });
+        LobbyManagerResponse::LobbyCreated(lobby_id)
     }
 
     fn join_lobby(&mut self, lobby_id: DraftLobbyId, player_name: String) -> LobbyManagerResponse {
@@ -152,6 +372,7 @@ impl LobbyManager {
             Some(lobby) => match lobby.add_player(player_name) {
                 Ok(player_id) => {
                     log::info!("Added {player_name_copy_for_logging} to lobby {lobby_id} with player_id {player_id}");
+                    self.log_event(LobbyEvent::PlayerJoined { lobby_id, player_id, player_name: player_name_copy_for_logging });
                     LobbyManagerResponse::LobbyJoined {
                         lobby_id,
                         player_id,
@@ -159,12 +380,30 @@ impl LobbyManager {
                 }
                 Err(e) => {
                     log::warn!("Failed to add {player_name_copy_for_logging} to lobby {lobby_id}: {e}");
-                    LobbyManagerResponse::LobbyErrorMsg(e.to_string())
+                    LobbyManagerResponse::LobbyError(e)
                 }
             },
             None => {
                 log::warn!("Player name {player_name} submitted to unknown lobby {lobby_id}");
-                LobbyManagerResponse::LobbyErrorMsg("Lobby not found".to_string())
+                LobbyManagerResponse::LobbyError(DraftError::LobbyNotFound)
+            }
+        }
+    }
+
+    fn add_bot(&mut self, lobby_id: DraftLobbyId, strategy: BotStrategy) -> LobbyManagerResponse {
+        let lobby = match self.active_lobbies.get_mut(&lobby_id) {
+            Some(lobby) => lobby,
+            None => return LobbyManagerResponse::LobbyError(DraftError::LobbyNotFound),
+        };
+        match lobby.add_bot(strategy) {
+            Ok((player_id, player_name)) => {
+                log::info!("Added bot {player_name} to lobby {lobby_id} with player_id {player_id}");
+                self.log_event(LobbyEvent::BotAdded { lobby_id, player_id, player_name, strategy });
+                LobbyManagerResponse::BotAdded
+            }
+            Err(e) => {
+                log::warn!("Failed to add a bot to lobby {lobby_id}: {e}");
+                LobbyManagerResponse::LobbyError(e)
             }
         }
     }
@@ -172,43 +411,52 @@ impl LobbyManager {
     fn start_lobby(&mut self, lobby_id: DraftLobbyId) -> LobbyManagerResponse {
         let start = match self.active_lobbies.get_mut(&lobby_id) {
             Some(lobby) => {
-                let draft_set = self.draft_database.get_set(lobby.get_set()).unwrap();
-                let draft_items = draft_set.get_item_list();
-                lobby.start(&draft_items)
+                let draft_set = self.lobby_sets.get(&lobby_id).unwrap();
+                let config = lobby.get_config().clone();
+                let draft_items = draft_set.get_item_list(lobby.get_rng(), &config);
+                let collation = draft_set.get_pack_collation(&draft_items);
+                lobby.deal_packs(&draft_items, &collation).and_then(|(turn_order, packs)| {
+                    let deadline = lobby.start(turn_order.clone(), packs.clone())?;
+                    Ok((deadline, LobbyEvent::LobbyStarted { lobby_id, turn_order, packs }))
+                })
             }
-            None => Err(io::Error::new(io::ErrorKind::NotFound, "Lobby not found"))
+            None => Err(DraftError::LobbyNotFound)
         };
         match start {
-            Ok(deadline) => {
-                log::info!("Started draft in lobby {lobby_id}");
+            Ok((deadline, event)) => {
+                self.log_event(event);
+                let set_name = self.active_lobbies.get(&lobby_id).unwrap().get_set();
+                log::info!("Started draft in lobby {lobby_id} with set {set_name}");
                 self.enqueue_deadline(lobby_id, deadline);
+                self.run_auto_picks(lobby_id);
                 LobbyManagerResponse::LobbyStarted
             }
             Err(e) => {
                 log::warn!("Failed to start lobby {lobby_id}: {e}");
-                LobbyManagerResponse::LobbyErrorMsg("Lobby did not start".to_string())
+                LobbyManagerResponse::LobbyError(e)
             }
         }
     }
 
-    fn get_lobby_state(&self, lobby_id: DraftLobbyId, player_id: PlayerId) -> io::Result<LobbyStateForPlayer> {
+    fn get_lobby_state(&self, lobby_id: DraftLobbyId, player_id: PlayerId) -> DraftResult<LobbyStateForPlayer> {
         let lobby = self.active_lobbies.get(&lobby_id);
         if lobby.is_none() {
-            return Err(io::Error::new(io::ErrorKind::NotFound, "Couldn't find lobby"));
+            return Err(DraftError::LobbyNotFound);
         }
         let lobby = lobby.unwrap();
-        let draft_set = self.draft_database.get_set(lobby.get_set()).unwrap();
+        let draft_set = self.lobby_sets.get(&lobby_id).unwrap();
 
         // If the draft is still waiting to start, show joining players and open slots
         let (joining_players, open_slots) = match lobby.draft_has_started() {
             true => (vec![], vec![]),
             false => {
                 let joining_players = lobby.get_player_names();
-                let num_open_slots = MAX_LOBBY_CAPACITY - joining_players.len();
+                let num_open_slots = lobby.get_capacity() - joining_players.len();
                 let open_slots = vec!["Open Slot".to_string(); num_open_slots];
                 (joining_players, open_slots)
             }
         };
+        let can_add_bots = !open_slots.is_empty() && lobby.get_format().picks_single_items();
 
         let (pending_picks, allocated_picks, raw_picks) = match lobby.get_player_draft_state(&player_id) {
             None => (vec![], vec![], vec![]),
@@ -238,7 +486,55 @@ impl LobbyManager {
             }
         };
 
+        // The shared pool, for players who aren't picking from it right now
+        let public_pool: Vec<(DraftItemId, String, String)> = match (lobby.get_public_pool(), pending_picks.is_empty()) {
+            (Some(pool), true) => pool.iter()
+                .map(|&item_id| {
+                    let item = draft_set.get_item_by_id(&item_id).unwrap();
+                    (item_id, item.get_template().clone(), item.get_stats().clone())
+                })
+                .collect(),
+            _ => vec![],
+        };
+
+        let current_bid = lobby.get_current_bid().map(|bid| {
+            let item = draft_set.get_item_by_id(&bid.item_id).unwrap();
+            CurrentBid {
+                template: item.get_template().clone(),
+                stats: item.get_stats().clone(),
+                high_bidder: lobby.get_player_name(&bid.high_bidder).unwrap().clone(),
+                amount: bid.amount,
+                max_bid: lobby.get_max_bid_for_player(&player_id),
+            }
+        });
+        let pick_queue = match lobby.get_format().picks_single_items() && !lobby.draft_is_finished() {
+            true => lobby.get_pick_queue(&player_id),
+            false => None,
+        };
+        let pick_queue_names: Vec<String> = pick_queue.iter()
+            .flat_map(|queue| &queue.preferences)
+            .map(|preference| match preference {
+                PickPreference::Item(item_id) => draft_set.get_item_by_id(item_id)
+                    .map_or("Unknown item".to_string(), |item| item.get_species().clone()),
+                PickPreference::Species(species) => format!("Any {species}"),
+            })
+            .collect();
+        let budgets = lobby.get_budgets();
+        let winston_piles = lobby.get_winston_piles(&player_id);
+        let grid: Vec<Option<(String, String)>> = lobby.get_grid()
+            .map(|grid| grid.iter()
+                .map(|cell| cell.map(|item_id| {
+                    let item = draft_set.get_item_by_id(&item_id).unwrap();
+                    (item.get_template().clone(), item.get_stats().clone())
+                }))
+                .collect())
+            .unwrap_or_default();
+        let can_take_line = !grid.is_empty() && lobby.get_current_player() == Some(player_id);
+        let current_turn = lobby.get_current_player()
+            .map(|current_player| lobby.get_player_name(&current_player).unwrap().clone());
+
         let game_state = lobby.compute_state(&player_id);
+        let seed = lobby.get_seed();
         let draft_is_finished = lobby.draft_is_finished();
 
         let time_to_pick_s = lobby.get_next_deadline_for_player(&player_id)
@@ -255,9 +551,20 @@ impl LobbyManager {
             player_id,
             joining_players,
             open_slots,
+            can_add_bots,
             pending_picks,
+            public_pool,
+            pick_queue,
+            pick_queue_names,
+            current_turn,
+            current_bid,
+            winston_piles,
+            grid,
+            can_take_line,
+            budgets,
             allocated_picks,
             game_state,
+            seed,
             draft_is_finished,
             time_to_pick_s,
             draft_order,
@@ -266,21 +573,242 @@ impl LobbyManager {
         });
     }
 
+    fn get_lobby_summary(&self, lobby_id: DraftLobbyId, player_id: PlayerId) -> DraftResult<LobbyStateSummary> {
+        let (lobby, draft_set) = match (self.active_lobbies.get(&lobby_id), self.lobby_sets.get(&lobby_id)) {
+            (Some(lobby), Some(draft_set)) => (lobby, draft_set),
+            _ => return Err(DraftError::LobbyNotFound),
+        };
+        let details = |item_ids: &[DraftItemId]| -> Vec<DraftItemDetails> {
+            item_ids.iter().filter_map(|item_id| draft_set.get_item_details(item_id)).collect()
+        };
+
+        let player_state = lobby.get_player_draft_state(&player_id);
+        let pending_picks = lobby.get_current_pack_contents_for_player(&player_id)
+            .map_or(vec![], |pack| details(pack));
+        let public_pool = lobby.get_public_pool()
+            .map_or(vec![], |pool| details(pool));
+        let picks = player_state.map_or(vec![], |state| details(&state.allocated_items));
+        let pick_queue = match lobby.get_format().picks_single_items() && !lobby.draft_is_finished() {
+            true => lobby.get_pick_queue(&player_id),
+            false => None,
+        };
+        let current_bid = lobby.get_current_bid().map(|bid| CurrentBidSummary {
+            item: draft_set.get_item_details(&bid.item_id).unwrap(),
+            high_bidder: lobby.get_player_name(&bid.high_bidder).unwrap().clone(),
+            amount: bid.amount,
+            max_bid: lobby.get_max_bid_for_player(&player_id),
+        });
+        let grid = lobby.get_grid()
+            .map(|grid| grid.iter()
+                .map(|cell| cell.and_then(|item_id| draft_set.get_item_details(&item_id)))
+                .collect())
+            .unwrap_or_default();
+        let progress = lobby.get_draft_progress_for_player(&player_id)
+            .map(|(round, total_rounds, pick, pack_size)| DraftProgress { round, total_rounds, pick, pack_size });
+        let time_to_pick_s = lobby.get_next_deadline_for_player(&player_id)
+            .and_then(|deadline| deadline.checked_duration_since(std::time::Instant::now()))
+            .map(|remaining_time| remaining_time.as_secs());
+
+        Ok(LobbyStateSummary {
+            lobby_id,
+            player_id,
+            set_name: lobby.get_set().clone(),
+            format: lobby.get_format(),
+            capacity: lobby.get_capacity(),
+            players: lobby.get_player_names(),
+            started: lobby.draft_has_started(),
+            finished: lobby.draft_is_finished(),
+            draft_order: lobby.get_draft_order(),
+            current_turn: lobby.get_current_player()
+                .map(|current_player| lobby.get_player_name(&current_player).unwrap().clone()),
+            pending_picks,
+            public_pool,
+            picks,
+            pick_queue,
+            current_bid,
+            budgets: lobby.get_budgets(),
+            winston_piles: lobby.get_winston_piles(&player_id),
+            grid,
+            progress,
+            time_to_pick_s,
+            game_state: lobby.compute_state(&player_id),
+            seed: lobby.get_seed(),
+        })
+    }
+
     fn make_pick(&mut self, lobby_id: DraftLobbyId, player_id: PlayerId, pick_id: DraftItemId) -> LobbyManagerResponse {
         let lobby = self.active_lobbies.get_mut(&lobby_id);
         if lobby.is_none() {
-            return LobbyManagerResponse::LobbyErrorMsg("Lobby doesn't exist".to_string());
+            return LobbyManagerResponse::LobbyError(DraftError::LobbyNotFound);
         }
         match lobby.unwrap().make_pick(player_id, pick_id) {
             Ok(maybe_deadline) => {
+                self.log_event(LobbyEvent::PickMade { lobby_id, player_id, pick: pick_id });
                 if maybe_deadline.is_some() {
                     self.enqueue_deadline(lobby_id, maybe_deadline.unwrap());
                 }
+                self.run_auto_picks(lobby_id);
                 LobbyManagerResponse::PickMade
             }
             Err(e) => {
                 log::warn!("Pick error @ [Lobby {lobby_id} Player {player_id} Picked {pick_id}]: {e}");
-                LobbyManagerResponse::LobbyErrorMsg("Error making pick".to_string())
+                LobbyManagerResponse::LobbyError(e)
+            }
+        }
+    }
+
+    fn nominate(&mut self, lobby_id: DraftLobbyId, player_id: PlayerId, pick_id: DraftItemId) -> LobbyManagerResponse {
+        let lobby = match self.active_lobbies.get_mut(&lobby_id) {
+            Some(lobby) => lobby,
+            None => return LobbyManagerResponse::LobbyError(DraftError::LobbyNotFound),
+        };
+        match lobby.nominate(player_id, pick_id) {
+            Ok(deadline) => {
+                self.log_event(LobbyEvent::ItemNominated { lobby_id, player_id, pick: pick_id });
+                if let Some(deadline) = deadline {
+                    self.enqueue_deadline(lobby_id, deadline);
+                }
+                LobbyManagerResponse::Nominated
+            }
+            Err(e) => {
+                log::warn!("Nomination error @ [Lobby {lobby_id} Player {player_id} Nominated {pick_id}]: {e}");
+                LobbyManagerResponse::LobbyError(e)
+            }
+        }
+    }
+
+    fn bid(&mut self, lobby_id: DraftLobbyId, player_id: PlayerId, amount: u32) -> LobbyManagerResponse {
+        let lobby = match self.active_lobbies.get_mut(&lobby_id) {
+            Some(lobby) => lobby,
+            None => return LobbyManagerResponse::LobbyError(DraftError::LobbyNotFound),
+        };
+        match lobby.bid(player_id, amount) {
+            Ok(deadline) => {
+                self.log_event(LobbyEvent::BidPlaced { lobby_id, player_id, amount });
+                if let Some(deadline) = deadline {
+                    self.enqueue_deadline(lobby_id, deadline);
+                }
+                LobbyManagerResponse::BidPlaced
+            }
+            Err(e) => {
+                log::warn!("Bid error @ [Lobby {lobby_id} Player {player_id} Bid {amount}]: {e}");
+                LobbyManagerResponse::LobbyError(e)
+            }
+        }
+    }
+
+    fn take_pile(&mut self, lobby_id: DraftLobbyId, player_id: PlayerId) -> LobbyManagerResponse {
+        let lobby = match self.active_lobbies.get_mut(&lobby_id) {
+            Some(lobby) => lobby,
+            None => return LobbyManagerResponse::LobbyError(DraftError::LobbyNotFound),
+        };
+        match lobby.take_pile(player_id) {
+            Ok(deadline) => {
+                self.log_event(LobbyEvent::PileTaken { lobby_id, player_id });
+                if let Some(deadline) = deadline {
+                    self.enqueue_deadline(lobby_id, deadline);
+                }
+                LobbyManagerResponse::PileTaken
+            }
+            Err(e) => {
+                log::warn!("Pile error @ [Lobby {lobby_id} Player {player_id} Took pile]: {e}");
+                LobbyManagerResponse::LobbyError(e)
+            }
+        }
+    }
+
+    fn pass_pile(&mut self, lobby_id: DraftLobbyId, player_id: PlayerId) -> LobbyManagerResponse {
+        let lobby = match self.active_lobbies.get_mut(&lobby_id) {
+            Some(lobby) => lobby,
+            None => return LobbyManagerResponse::LobbyError(DraftError::LobbyNotFound),
+        };
+        match lobby.pass_pile(player_id) {
+            Ok(deadline) => {
+                self.log_event(LobbyEvent::PilePassed { lobby_id, player_id });
+                if let Some(deadline) = deadline {
+                    self.enqueue_deadline(lobby_id, deadline);
+                }
+                LobbyManagerResponse::PilePassed
+            }
+            Err(e) => {
+                log::warn!("Pile error @ [Lobby {lobby_id} Player {player_id} Passed pile]: {e}");
+                LobbyManagerResponse::LobbyError(e)
+            }
+        }
+    }
+
+    fn take_line(&mut self, lobby_id: DraftLobbyId, player_id: PlayerId, line: GridLine) -> LobbyManagerResponse {
+        let lobby = match self.active_lobbies.get_mut(&lobby_id) {
+            Some(lobby) => lobby,
+            None => return LobbyManagerResponse::LobbyError(DraftError::LobbyNotFound),
+        };
+        match lobby.take_line(player_id, line) {
+            Ok(deadline) => {
+                self.log_event(LobbyEvent::LineTaken { lobby_id, player_id, line });
+                if let Some(deadline) = deadline {
+                    self.enqueue_deadline(lobby_id, deadline);
+                }
+                LobbyManagerResponse::LineTaken
+            }
+            Err(e) => {
+                log::warn!("Grid error @ [Lobby {lobby_id} Player {player_id} Took {line:?}]: {e}");
+                LobbyManagerResponse::LobbyError(e)
+            }
+        }
+    }
+
+    fn set_pick_queue(&mut self, lobby_id: DraftLobbyId, player_id: PlayerId, queue: PickQueue) -> LobbyManagerResponse {
+        let (lobby, draft_set) = match (self.active_lobbies.get_mut(&lobby_id), self.lobby_sets.get(&lobby_id)) {
+            (Some(lobby), Some(draft_set)) => (lobby, draft_set),
+            _ => return LobbyManagerResponse::LobbyError(DraftError::LobbyNotFound),
+        };
+        for preference in &queue.preferences {
+            let in_set = match preference {
+                PickPreference::Item(item_id) => draft_set.get_item_by_id(item_id).is_some(),
+                PickPreference::Species(species) => draft_set.has_species(species),
+            };
+            if !in_set {
+                return LobbyManagerResponse::LobbyError(DraftError::NotInSet(format!("Can't queue {preference:?}, it isn't in this set")));
+            }
+        }
+        match lobby.set_pick_queue(player_id, queue.clone()) {
+            Ok(_) => {
+                self.log_event(LobbyEvent::PickQueueSet { lobby_id, player_id, queue });
+                self.run_auto_picks(lobby_id);
+                LobbyManagerResponse::PickQueueSet
+            }
+            Err(e) => {
+                log::warn!("Pick queue error @ [Lobby {lobby_id} Player {player_id}]: {e}");
+                LobbyManagerResponse::LobbyError(e)
+            }
+        }
+    }
+
+    fn stream_events(&mut self, lobby_id: DraftLobbyId, player_id: PlayerId) -> LobbyManagerResponse {
+        match self.active_lobbies.get_mut(&lobby_id) {
+            Some(lobby) => {
+                let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+                lobby.add_event_stream(player_id, tx);
+                LobbyManagerResponse::EventStream(rx)
+            }
+            None => LobbyManagerResponse::LobbyError(DraftError::LobbyNotFound),
+        }
+    }
+
+    fn reload_sets(&mut self) -> LobbyManagerResponse {
+        match self.draft_database.reload() {
+            Ok(draft_database) => {
+                self.draft_database = draft_database;
+                let num_sets = self.draft_database.list_sets().len();
+                log::info!("Reloaded {num_sets} draft sets");
+                LobbyManagerResponse::SetsReloaded { num_sets }
+            }
+            Err(problems) => {
+                let problems: Vec<String> = problems.iter().map(|problem| problem.to_string()).collect();
+                for problem in &problems {
+                    log::error!("Failed to reload draft sets: {problem}");
+                }
+                LobbyManagerResponse::LobbyError(DraftError::InvalidSets(problems.join("\n")))
             }
         }
     }
@@ -312,15 +840,272 @@ impl LobbyManager {
         }).ignore();
     }
 
-    fn enforce_deadline(&mut self, lobby_id: DraftLobbyId, round_idx: usize, pick_idx: usize) -> io::Result<()> {
+    fn enforce_deadline(&mut self, lobby_id: DraftLobbyId, round_idx: usize, pick_idx: usize) -> DraftResult<()> {
         if !self.active_lobbies.contains_key(&lobby_id) {
-            return Err(io::Error::new(io::ErrorKind::NotFound, "Couldn't find lobby"));
+            return Err(DraftError::LobbyNotFound);
         }
         let lobby = self.active_lobbies.get_mut(&lobby_id).unwrap();
-        match lobby.enforce_deadline(round_idx, pick_idx)? {
+        match lobby.get_format() {
+            DraftFormat::Auction => return self.enforce_auction_deadline(lobby_id, pick_idx),
+            DraftFormat::Winston => return self.enforce_winston_deadline(lobby_id, pick_idx),
+            DraftFormat::Grid => return self.enforce_grid_deadline(lobby_id, pick_idx),
+            _ => (),
+        }
+        let draft_set = self.lobby_sets.get(&lobby_id).unwrap().clone();
+        let mut auto_pick = |pack: &Vec<DraftItemId>, player_state: &PlayerState, rng: &mut StdRng|
+            bots::find_queued_item(&player_state.pick_queue, pack, &draft_set)
+                .unwrap_or_else(|| HeuristicPick.choose(pack, &player_state.allocated_items, &draft_set, rng));
+        let (new_deadline, forced_picks) = lobby.enforce_deadline(round_idx, pick_idx, &mut auto_pick)?;
+        for (player_id, pick) in forced_picks {
+            self.log_event(LobbyEvent::PickForced { lobby_id, player_id, pick });
+        }
+        match new_deadline {
             Some(new_deadline) => self.enqueue_deadline(lobby_id, new_deadline),
             _ => ()
         }
+        self.run_auto_picks(lobby_id);
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Makes the picks that don't wait for anyone: every bot picks from the pack in front of it, and so does every
+    /// player whose pick queue is set to pick immediately and has an item in that pack. Repeats until nobody can pick,
+    /// since one pick can pass a pack on or make it someone else's turn.
+    fn run_auto_picks(&mut self, lobby_id: DraftLobbyId) {
+        let draft_set = match self.lobby_sets.get(&lobby_id) {
+            Some(draft_set) => draft_set.clone(),
+            None => return,
+        };
+        loop {
+            let lobby = match self.active_lobbies.get_mut(&lobby_id) {
+                Some(lobby) => lobby,
+                None => return,
+            };
+            // Players are checked in join order, to keep seeded drafts with bots reproducible
+            let next_player = lobby.get_player_ids().iter().copied().find(|player_id| {
+                let pack = match lobby.get_current_pack_contents_for_player(player_id) {
+                    Some(pack) if !pack.is_empty() => pack,
+                    _ => return false,
+                };
+                lobby.get_bots().contains_key(player_id) || lobby.get_player_draft_state(player_id)
+                    .is_some_and(|state| state.pick_queue.pick_immediately
+                        && bots::find_queued_item(&state.pick_queue, pack, &draft_set).is_some())
+            });
+            let player_id = match next_player {
+                Some(player_id) => player_id,
+                None => return,
+            };
+            let pack = lobby.get_current_pack_contents_for_player(&player_id).unwrap().clone();
+            let player_state = lobby.get_player_draft_state(&player_id).unwrap();
+            let pick = match lobby.get_bots().get(&player_id).copied() {
+                Some(strategy) => {
+                    let drafted = player_state.allocated_items.clone();
+                    strategy.pick_strategy().choose(&pack, &drafted, &draft_set, lobby.get_rng())
+                }
+                None => bots::find_queued_item(&player_state.pick_queue, &pack, &draft_set).unwrap(),
+            };
+            match lobby.make_pick(player_id, pick) {
+                Ok(maybe_deadline) => {
+                    self.log_event(LobbyEvent::PickMade { lobby_id, player_id, pick });
+                    if let Some(deadline) = maybe_deadline {
+                        self.enqueue_deadline(lobby_id, deadline);
+                    }
+                }
+                Err(e) => {
+                    log::error!("Player {player_id} in lobby {lobby_id} couldn't auto-pick {pick}: {e}");
+                    return;
+                }
+            }
+        }
+    }
+
+    fn enforce_auction_deadline(&mut self, lobby_id: DraftLobbyId, turn_idx: usize) -> DraftResult<()> {
+        let lobby = self.active_lobbies.get_mut(&lobby_id).unwrap();
+        let (new_deadline, timeout) = lobby.enforce_auction_deadline(turn_idx)?;
+        match timeout {
+            Some(AuctionTimeout::Nominated { player_id, item_id }) =>
+                self.log_event(LobbyEvent::ItemNominated { lobby_id, player_id, pick: item_id }),
+            Some(AuctionTimeout::Sold { player_id, item_id, price }) => {
+                log::info!("Sold item {item_id} to player {player_id} for {price} in lobby {lobby_id}");
+                self.log_event(LobbyEvent::ItemSold { lobby_id, player_id, pick: item_id, price });
+            }
+            None => (),
+        }
+        if let Some(new_deadline) = new_deadline {
+            self.enqueue_deadline(lobby_id, new_deadline);
+        }
+        Ok(())
+    }
+
+    fn enforce_winston_deadline(&mut self, lobby_id: DraftLobbyId, turn_idx: usize) -> DraftResult<()> {
+        let lobby = self.active_lobbies.get_mut(&lobby_id).unwrap();
+        let (new_deadline, timed_out_player) = lobby.enforce_winston_deadline(turn_idx)?;
+        if let Some(player_id) = timed_out_player {
+            self.log_event(LobbyEvent::PileTaken { lobby_id, player_id });
+        }
+        if let Some(new_deadline) = new_deadline {
+            self.enqueue_deadline(lobby_id, new_deadline);
+        }
+        Ok(())
+    }
+
+    fn enforce_grid_deadline(&mut self, lobby_id: DraftLobbyId, turn_idx: usize) -> DraftResult<()> {
+        let lobby = self.active_lobbies.get_mut(&lobby_id).unwrap();
+        let (new_deadline, timeout) = lobby.enforce_grid_deadline(turn_idx)?;
+        if let Some(GridTimeout { player_id, line }) = timeout {
+            self.log_event(LobbyEvent::LineTaken { lobby_id, player_id, line });
+        }
+        if let Some(new_deadline) = new_deadline {
+            self.enqueue_deadline(lobby_id, new_deadline);
+        }
+        Ok(())
+    }
+
+    fn log_event(&mut self, event: LobbyEvent) {
+        if let Some(event_log) = self.event_log.as_mut() {
+            if let Err(e) = event_log.append(&event) {
+                log::error!("Failed to write {event:?} to the event log: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_fixtures::{temp_data_folder, tiered_sets, write_set, DATA_FOLDER, TIERED_MANIFEST};
+
+    /// A lobby manager for the draft sets in a data folder, without an event log
+    fn make_manager(data_folder: &str) -> LobbyManager {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        LobbyManager::new(rx, tx, DraftDb::from_folder(data_folder).unwrap(), None)
+    }
+
+    /// Creates a lobby with the given options, and joins the given players to it in order
+    fn create_lobby(manager: &mut LobbyManager, set_name: &str, options: LobbyOptions, players: &[&str]) -> (DraftLobbyId, Vec<PlayerId>) {
+        let lobby_id = match manager.create_lobby(set_name.to_string(), Some(1), options) {
+            LobbyManagerResponse::LobbyCreated(lobby_id) => lobby_id,
+            LobbyManagerResponse::LobbyError(e) => panic!("Couldn't create a lobby: {e}"),
+            _ => panic!("Couldn't create a lobby"),
+        };
+        let player_ids = players.iter()
+            .map(|name| match manager.join_lobby(lobby_id, name.to_string()) {
+                LobbyManagerResponse::LobbyJoined { player_id, .. } => player_id,
+                _ => panic!("{name} couldn't join the lobby"),
+            })
+            .collect();
+        (lobby_id, player_ids)
+    }
+
+    #[test]
+    fn rochester_players_all_see_the_open_pack_and_whose_turn_it_is() {
+        let mut manager = make_manager(DATA_FOLDER);
+        let options = LobbyOptions { format: Some(DraftFormat::Rochester), capacity: Some(3), num_rounds: Some(1), pack_size: Some(6), ..LobbyOptions::default() };
+        let (lobby_id, player_ids) = create_lobby(&mut manager, "all_stars", options, &["Alice", "Bob", "Carol"]);
+        assert!(matches!(manager.start_lobby(lobby_id), LobbyManagerResponse::LobbyStarted));
+        let views = |manager: &LobbyManager| -> Vec<(Vec<DraftItemId>, Option<String>)> {
+            player_ids.iter()
+                .map(|&player_id| {
+                    // The player whose turn it is sees the pack as their picks, and everyone else as the public pool
+                    let state = manager.get_lobby_state(lobby_id, player_id).unwrap();
+                    let pack = match state.pending_picks.is_empty() {
+                        true => &state.public_pool,
+                        false => &state.pending_picks,
+                    };
+                    (pack.iter().map(|(item_id, _, _)| *item_id).collect(), state.current_turn)
+                })
+                .collect()
+        };
+
+        let before = views(&manager);
+        assert_eq!(before[0].0.len(), 6);
+        assert_eq!(before[0].1.as_deref(), Some("Alice"));
+        assert!(before.iter().all(|view| view == &before[0]));
+
+        let pick = before[0].0[0];
+        assert!(matches!(manager.make_pick(lobby_id, player_ids[1], pick), LobbyManagerResponse::LobbyError(DraftError::NotYourTurn)));
+        assert!(matches!(manager.make_pick(lobby_id, player_ids[0], pick), LobbyManagerResponse::PickMade));
+        let after = views(&manager);
+        assert_eq!(after[0].0, before[0].0[1..]);
+        assert_eq!(after[0].1.as_deref(), Some("Bob"));
+        assert!(after.iter().all(|view| view == &after[0]));
+    }
+
+    #[test]
+    fn only_creates_lobbies_the_pack_collation_can_fill() {
+        let data_dir = temp_data_folder("manager-collation");
+        write_set(&data_dir.join("tiered"), &tiered_sets(), Some(TIERED_MANIFEST));
+        let mut manager = make_manager(data_dir.to_str().unwrap());
+        let options = |capacity| LobbyOptions { capacity: Some(capacity), num_rounds: Some(1), pack_size: Some(4), ..LobbyOptions::default() };
+        // Three players would need three tier S items
+        assert!(matches!(manager.create_lobby("tiered".to_string(), None, options(3)),
+                         LobbyManagerResponse::LobbyError(DraftError::NotEnoughItems(_))));
+
+        let (lobby_id, player_ids) = create_lobby(&mut manager, "tiered", options(2), &["Alice", "Bob"]);
+        assert!(matches!(manager.start_lobby(lobby_id), LobbyManagerResponse::LobbyStarted));
+        let draft_set = manager.lobby_sets.get(&lobby_id).unwrap().clone();
+        for player_id in player_ids {
+            let pack = manager.get_lobby_state(lobby_id, player_id).unwrap().pending_picks;
+            let tiers: Vec<Option<&String>> = pack.iter()
+                .map(|(item_id, _, _)| draft_set.get_item_by_id(item_id).unwrap().get_tier())
+                .collect();
+            assert_eq!(tiers.len(), 4);
+            assert_eq!(tiers.iter().filter(|tier| tier.is_some_and(|tier| tier == "S")).count(), 1);
+            assert_eq!(tiers.iter().filter(|tier| tier.is_some_and(|tier| tier == "A")).count(), 2);
+        }
+    }
+
+    #[test]
+    fn rejects_lobbies_banning_something_not_in_the_set() {
+        let mut manager = make_manager(DATA_FOLDER);
+        let options = LobbyOptions { banned: vec!["Not A Pokemon".to_string()], ..LobbyOptions::default() };
+        assert!(matches!(manager.create_lobby("all_stars".to_string(), None, options),
+                         LobbyManagerResponse::LobbyError(DraftError::NotInSet(_))));
+    }
+
+    #[test]
+    fn bots_draft_a_whole_lobby_on_their_own() {
+        for format in [DraftFormat::Booster, DraftFormat::Snake, DraftFormat::Rochester] {
+            let mut manager = make_manager(DATA_FOLDER);
+            let options = LobbyOptions { format: Some(format), capacity: Some(3), num_rounds: Some(2), pack_size: Some(6), ..LobbyOptions::default() };
+            let (lobby_id, _) = create_lobby(&mut manager, "all_stars", options, &[]);
+            for strategy in [BotStrategy::Random, BotStrategy::FirstItem, BotStrategy::Heuristic] {
+                assert!(matches!(manager.add_bot(lobby_id, strategy), LobbyManagerResponse::BotAdded));
+            }
+            assert!(matches!(manager.start_lobby(lobby_id), LobbyManagerResponse::LobbyStarted));
+
+            let lobby = manager.active_lobbies.get(&lobby_id).unwrap();
+            assert!(lobby.draft_is_finished(), "{format:?} draft didn't finish");
+            let team_sizes: Vec<usize> = lobby.get_player_ids().iter()
+                .map(|player_id| lobby.get_player_draft_state(player_id).unwrap().allocated_items.len())
+                .collect();
+            assert_eq!(team_sizes, vec![12, 12, 12]);
+        }
+    }
+
+    #[test]
+    fn deadlines_pick_queued_items_before_the_heuristic() {
+        let mut manager = make_manager(DATA_FOLDER);
+        let options = LobbyOptions { capacity: Some(2), ..LobbyOptions::default() };
+        let (lobby_id, player_ids) = create_lobby(&mut manager, "all_stars", options, &["Alice", "Bob"]);
+        assert!(matches!(manager.start_lobby(lobby_id), LobbyManagerResponse::LobbyStarted));
+        let draft_set = manager.lobby_sets.get(&lobby_id).unwrap().clone();
+        let heuristic_picks: Vec<DraftItemId> = player_ids.iter()
+            .map(|player_id| {
+                let pack = manager.active_lobbies.get(&lobby_id).unwrap().get_current_pack_contents_for_player(player_id).unwrap();
+                HeuristicPick.choose(pack, &[], &draft_set, &mut rand::SeedableRng::seed_from_u64(1))
+            })
+            .collect();
+
+        // Alice queues an item the heuristic wouldn't pick, and Bob queues nothing
+        let alice_pack = manager.get_lobby_state(lobby_id, player_ids[0]).unwrap().pending_picks;
+        let queued = alice_pack.iter().map(|(item_id, _, _)| *item_id).find(|&item_id| item_id != heuristic_picks[0]).unwrap();
+        let queue = PickQueue { preferences: vec![PickPreference::Item(queued)], pick_immediately: false };
+        assert!(matches!(manager.set_pick_queue(lobby_id, player_ids[0], queue), LobbyManagerResponse::PickQueueSet));
+        manager.enforce_deadline(lobby_id, 0, 0).unwrap();
+
+        let lobby = manager.active_lobbies.get(&lobby_id).unwrap();
+        assert_eq!(lobby.get_player_draft_state(&player_ids[0]).unwrap().allocated_items, vec![queued]);
+        assert_eq!(lobby.get_player_draft_state(&player_ids[1]).unwrap().allocated_items, vec![heuristic_picks[1]]);
+    }
+}
@@ -8,7 +8,16 @@ use crate::lobby_manager::{LobbyManagerResponse};
 mod lobby_manager;
 mod draft_engine;
 mod draft_database;
+mod pokemon_set;
+mod dex;
+mod set_renderer;
+mod stats;
+mod validation;
+mod event_log;
 mod routes;
+mod bots;
+#[cfg(test)]
+mod test_fixtures;
 
 
 #[tokio::main]
@@ -20,6 +29,24 @@ async fn main() {
         .env().init()
         .unwrap();
 
+    // `pkmn-draft validate [data folder]` checks the draft sets instead of starting the server
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).map(|arg| arg.as_str()) == Some("validate") {
+        let data_folder = args.get(2).cloned().unwrap_or("data".to_string());
+        match validation::validate_data_folder(&data_folder) {
+            Ok(0) => log::info!("All draft sets are valid"),
+            Ok(num_problems) => {
+                log::error!("Found {num_problems} problems in draft sets");
+                std::process::exit(1);
+            }
+            Err(e) => {
+                log::error!("Failed to validate draft sets: {e}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
     let configured_addr: SocketAddr = match env::var("PKMNDRAFT_PORT") {
         Ok(val) => {
             let parsed_port: u16 = val.trim().parse().unwrap();
@@ -41,6 +68,9 @@ async fn main() {
         Err(_) => None
     };
 
+    // Setting PKMNDRAFT_ADMIN_TOKEN enables the admin endpoints, which need the token in an `X-Admin-Token` header
+    let admin_token: Option<String> = env::var("PKMNDRAFT_ADMIN_TOKEN").ok();
+
     let https_credentials = if cert_path.is_some() && key_path.is_some() {
         log::info!("Found HTTPS credentials.");
         Some((cert_path.unwrap(), key_path.unwrap()))
@@ -49,13 +79,38 @@ async fn main() {
     };
 
 
-    // let database = draft_database::DraftSet::from_folder("data/all_stars").unwrap();
-    let database = draft_database::DraftDb::from_folder("data").unwrap();
+    // Setting PKMNDRAFT_STRICT_SETS (to anything but 0 or false) refuses to start the server if any draft set is broken
+    let database = match env::var("PKMNDRAFT_STRICT_SETS") {
+        Ok(val) if val != "0" && !val.eq_ignore_ascii_case("false") => match draft_database::DraftDb::from_folder_strict("data") {
+            Ok(db) => db,
+            Err(problems) => {
+                for problem in problems {
+                    log::error!("{problem}");
+                }
+                log::error!("Refusing to start with broken draft sets");
+                std::process::exit(1);
+            }
+        },
+        _ => draft_database::DraftDb::from_folder("data").unwrap(),
+    };
 
     let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
     let (mpsc_tx, mpsc_rx): (tokio::sync::mpsc::Sender<lobby_manager::LobbyManagerTask>, tokio::sync::mpsc::Receiver<lobby_manager::LobbyManagerTask>) = tokio::sync::mpsc::channel(1);
     let lobby_manager_self_queue = mpsc_tx.clone();
-    let mut lobby_manager = lobby_manager::LobbyManager::new(mpsc_rx, lobby_manager_self_queue, database);
+    let reload_signal_queue = mpsc_tx.clone();
+    // Setting PKMNDRAFT_EVENT_LOG keeps lobbies across restarts, by logging every change to them to that file
+    let (event_log, past_events) = match env::var("PKMNDRAFT_EVENT_LOG") {
+        Ok(path) => match event_log::EventLog::open(&path) {
+            Ok((event_log, past_events)) => (Some(event_log), past_events),
+            Err(e) => {
+                log::error!("Failed to open event log {path}: {e}");
+                std::process::exit(1);
+            }
+        },
+        Err(_) => (None, vec![]),
+    };
+    let mut lobby_manager = lobby_manager::LobbyManager::new(mpsc_rx, lobby_manager_self_queue, database, event_log);
+    lobby_manager.replay_events(past_events);
 
     let lobby_manager_thread = std::thread::spawn(move || {
         lobby_manager.run();
@@ -67,17 +122,19 @@ async fn main() {
 
     match https_credentials {
         Some(credentials) => {
-            let webserver = routes::make_server_with_tls(configured_addr, credentials, mpsc_tx, shutdown_rx);
+            let webserver = routes::make_server_with_tls(configured_addr, credentials, admin_token, mpsc_tx, shutdown_rx);
             tokio::task::spawn(webserver);
             let redirect_server = routes::make_https_redirect_server();
             tokio::task::spawn(redirect_server);
         },
         None => {
-            let webserver = routes::make_server(configured_addr, mpsc_tx, shutdown_rx);
+            let webserver = routes::make_server(configured_addr, admin_token, mpsc_tx, shutdown_rx);
             tokio::task::spawn(webserver);
         }
     };
 
+    tokio::task::spawn(reload_sets_on_sighup(reload_signal_queue));
+
     log::info!("Server Ready");
 
     match lobby_manager_thread.join() {
@@ -85,3 +142,29 @@ async fn main() {
         Err(_) => log::error!("Lobby manager did not exit gracefully")
     }
 }
+
+/// Reloads the draft sets from disk every time the server receives a SIGHUP
+async fn reload_sets_on_sighup(lobby_manager_task_queue: tokio::sync::mpsc::Sender<lobby_manager::LobbyManagerTask>) {
+    let mut hangups = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+        Ok(hangups) => hangups,
+        Err(e) => {
+            log::error!("Failed to listen for SIGHUP: {e}");
+            return;
+        }
+    };
+    while hangups.recv().await.is_some() {
+        log::info!("Received SIGHUP, reloading draft sets");
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let task = lobby_manager::LobbyManagerTask {
+            request: lobby_manager::LobbyManagerRequest::ReloadSets,
+            response_channel: tx,
+        };
+        if let Err(e) = lobby_manager_task_queue.send(task).await {
+            log::error!("Failed to enqueue task: {e}");
+            continue;
+        }
+        if let Ok(LobbyManagerResponse::LobbyError(_)) = rx.await {
+            log::warn!("Kept the previous draft sets, as the reload failed");
+        }
+    }
+}
@@ -0,0 +1,463 @@
+use std::fmt;
+
+use serde_derive::{Deserialize, Serialize};
+
+pub const DEFAULT_EV: u16 = 0;
+pub const DEFAULT_IV: u16 = 31;
+pub const DEFAULT_LEVEL: u8 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum Stat {
+    Hp,
+    Atk,
+    Def,
+    SpA,
+    SpD,
+    Spe,
+}
+
+pub const ALL_STATS: [Stat; 6] = [Stat::Hp, Stat::Atk, Stat::Def, Stat::SpA, Stat::SpD, Stat::Spe];
+
+impl Stat {
+    /// The label used for this stat in the Showdown export format, e.g. "SpA"
+    pub fn label(&self) -> &'static str {
+        match self {
+            Stat::Hp => "HP",
+            Stat::Atk => "Atk",
+            Stat::Def => "Def",
+            Stat::SpA => "SpA",
+            Stat::SpD => "SpD",
+            Stat::Spe => "Spe",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Stat> {
+        ALL_STATS.iter().find(|stat| stat.label().eq_ignore_ascii_case(label)).copied()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatSpread {
+    pub hp: u16,
+    pub atk: u16,
+    pub def: u16,
+    pub spa: u16,
+    pub spd: u16,
+    pub spe: u16,
+}
+
+impl StatSpread {
+    pub fn uniform(value: u16) -> StatSpread {
+        StatSpread { hp: value, atk: value, def: value, spa: value, spd: value, spe: value }
+    }
+
+    pub fn get(&self, stat: Stat) -> u16 {
+        match stat {
+            Stat::Hp => self.hp,
+            Stat::Atk => self.atk,
+            Stat::Def => self.def,
+            Stat::SpA => self.spa,
+            Stat::SpD => self.spd,
+            Stat::Spe => self.spe,
+        }
+    }
+
+    pub fn set(&mut self, stat: Stat, value: u16) {
+        match stat {
+            Stat::Hp => self.hp = value,
+            Stat::Atk => self.atk = value,
+            Stat::Def => self.def = value,
+            Stat::SpA => self.spa = value,
+            Stat::SpD => self.spd = value,
+            Stat::Spe => self.spe = value,
+        }
+    }
+
+    /// The sum of every stat. Parsed spreads can have any `u16`, so this is wider to not overflow.
+    pub fn total(&self) -> u32 {
+        ALL_STATS.iter().map(|&stat| u32::from(self.get(stat))).sum()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Gender {
+    M,
+    F,
+}
+
+/// A single Pokemon set, as written in the standard Showdown export format.
+#[derive(Debug, Clone, Serialize)]
+pub struct PokemonSet {
+    pub nickname: Option<String>,
+    pub species: String,
+    pub item: Option<String>,
+    pub ability: Option<String>,
+    pub evs: StatSpread,
+    pub ivs: StatSpread,
+    pub nature: Option<String>,
+    pub moves: Vec<String>,
+    pub level: u8,
+    pub shiny: bool,
+    pub gender: Option<Gender>,
+    pub happiness: Option<u8>,
+    pub tera_type: Option<String>,
+    /// The set's rarity tier for pack collation, from a `Tier:` line. Not part of the Showdown export format.
+    pub tier: Option<String>,
+    /// 1-indexed line of the source file that this set starts on
+    pub line_number: usize,
+    #[serde(skip)]
+    pub lines: SetLines,
+}
+
+/// The source line of each part of a set after its first line, for reporting problems in the set
+#[derive(Debug, Clone, Default)]
+pub struct SetLines {
+    pub ability: Option<usize>,
+    pub level: Option<usize>,
+    pub tera_type: Option<usize>,
+    pub evs: Option<usize>,
+    pub nature: Option<usize>,
+    pub ivs: Option<usize>,
+    pub moves: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetParseError {
+    pub line_number: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SetParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line_number, self.message)
+    }
+}
+
+impl std::error::Error for SetParseError {}
+
+impl PokemonSet {
+    fn new(species: String, line_number: usize) -> PokemonSet {
+        PokemonSet {
+            nickname: None,
+            species,
+            item: None,
+            ability: None,
+            evs: StatSpread::uniform(DEFAULT_EV),
+            ivs: StatSpread::uniform(DEFAULT_IV),
+            nature: None,
+            moves: vec![],
+            level: DEFAULT_LEVEL,
+            shiny: false,
+            gender: None,
+            happiness: None,
+            tera_type: None,
+            tier: None,
+            line_number,
+            lines: SetLines::default(),
+        }
+    }
+
+    /// Parses every set in a Showdown export. Sets are separated by one or more blank lines.
+    pub fn parse_all(text: &str) -> Result<Vec<PokemonSet>, SetParseError> {
+        let mut sets = vec![];
+        let mut current_block: Vec<(usize, &str)> = vec![];
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                if !current_block.is_empty() {
+                    sets.push(PokemonSet::parse_block(&current_block)?);
+                    current_block.clear();
+                }
+                continue;
+            }
+            current_block.push((idx + 1, line));
+        }
+        if !current_block.is_empty() {
+            sets.push(PokemonSet::parse_block(&current_block)?);
+        }
+        Ok(sets)
+    }
+
+    fn parse_block(lines: &[(usize, &str)]) -> Result<PokemonSet, SetParseError> {
+        let &(first_line_number, first_line) = lines.first().unwrap();
+        let mut set = parse_header(first_line, first_line_number)?;
+
+        for &(line_number, line) in &lines[1..] {
+            let error = |message: String| SetParseError { line_number, message };
+            if let Some(pokemon_move) = line.strip_prefix('-') {
+                set.moves.push(pokemon_move.trim().to_string());
+                set.lines.moves.push(line_number);
+            } else if let Some(nature) = line.strip_suffix(" Nature") {
+                set.nature = Some(nature.trim().to_string());
+                set.lines.nature = Some(line_number);
+            } else if let Some((key, value)) = line.split_once(':') {
+                let value = value.trim();
+                match key.trim() {
+                    "Ability" => {
+                        set.ability = Some(value.to_string());
+                        set.lines.ability = Some(line_number);
+                    }
+                    "EVs" => {
+                        set.evs = parse_spread(value, DEFAULT_EV).map_err(error)?;
+                        set.lines.evs = Some(line_number);
+                    }
+                    "IVs" => {
+                        set.ivs = parse_spread(value, DEFAULT_IV).map_err(error)?;
+                        set.lines.ivs = Some(line_number);
+                    }
+                    "Level" => {
+                        set.level = value.parse()
+                            .map_err(|_| error(format!("Invalid level '{value}'")))?;
+                        set.lines.level = Some(line_number);
+                    }
+                    "Shiny" => set.shiny = value.eq_ignore_ascii_case("yes"),
+                    "Happiness" => set.happiness = Some(value.parse()
+                        .map_err(|_| error(format!("Invalid happiness '{value}'")))?),
+                    "Tera Type" => {
+                        set.tera_type = Some(value.to_string());
+                        set.lines.tera_type = Some(line_number);
+                    }
+                    "Tier" => set.tier = Some(value.to_string()),
+                    other => return Err(error(format!("Unrecognised attribute '{other}'"))),
+                }
+            } else {
+                return Err(error(format!("Unrecognised line '{line}'")));
+            }
+        }
+        Ok(set)
+    }
+}
+
+/// Parses the `Nickname (Species) (M) @ Item` line that starts every set
+fn parse_header(line: &str, line_number: usize) -> Result<PokemonSet, SetParseError> {
+    let (names, item) = match line.rsplit_once(" @ ") {
+        Some((names, item)) => (names.trim(), Some(item.trim().to_string())),
+        None => (line.trim(), None),
+    };
+
+    let (names, gender) = if let Some(names) = names.strip_suffix(" (M)") {
+        (names, Some(Gender::M))
+    } else if let Some(names) = names.strip_suffix(" (F)") {
+        (names, Some(Gender::F))
+    } else {
+        (names, None)
+    };
+
+    let (nickname, species) = match names.strip_suffix(')').and_then(|n| n.rsplit_once(" (")) {
+        Some((nickname, species)) => (Some(nickname.trim().to_string()), species.trim()),
+        None => (None, names),
+    };
+    if species.is_empty() {
+        return Err(SetParseError { line_number, message: "Set has no species".to_string() });
+    }
+
+    let mut set = PokemonSet::new(species.to_string(), line_number);
+    set.nickname = nickname;
+    set.gender = gender;
+    set.item = item;
+    Ok(set)
+}
+
+/// Parses a spread such as `252 HP / 4 SpD / 252 Spe`, filling unlisted stats with a default
+fn parse_spread(value: &str, default: u16) -> Result<StatSpread, String> {
+    let mut spread = StatSpread::uniform(default);
+    for entry in value.split('/') {
+        let (amount, label) = entry.trim().split_once(' ')
+            .ok_or(format!("Invalid stat entry '{}'", entry.trim()))?;
+        let stat = Stat::from_label(label.trim())
+            .ok_or(format!("Unknown stat '{}'", label.trim()))?;
+        let amount: u16 = amount.parse()
+            .map_err(|_| format!("Invalid stat value '{amount}'"))?;
+        spread.set(stat, amount);
+    }
+    Ok(spread)
+}
+
+fn format_spread(spread: &StatSpread, default: u16) -> Option<String> {
+    let entries: Vec<String> = ALL_STATS.iter()
+        .filter(|&&stat| spread.get(stat) != default)
+        .map(|&stat| format!("{} {}", spread.get(stat), stat.label()))
+        .collect();
+    match entries.is_empty() {
+        true => None,
+        false => Some(entries.join(" / ")),
+    }
+}
+
+impl fmt::Display for Gender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Gender::M => write!(f, "M"),
+            Gender::F => write!(f, "F"),
+        }
+    }
+}
+
+/// Writes the set back out in Showdown export format
+impl fmt::Display for PokemonSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.nickname {
+            Some(nickname) => write!(f, "{} ({})", nickname, self.species)?,
+            None => write!(f, "{}", self.species)?,
+        }
+        if let Some(gender) = &self.gender {
+            write!(f, " ({gender})")?;
+        }
+        if let Some(item) = &self.item {
+            write!(f, " @ {item}")?;
+        }
+        writeln!(f)?;
+        if let Some(ability) = &self.ability {
+            writeln!(f, "Ability: {ability}")?;
+        }
+        if self.level != DEFAULT_LEVEL {
+            writeln!(f, "Level: {}", self.level)?;
+        }
+        if self.shiny {
+            writeln!(f, "Shiny: Yes")?;
+        }
+        if let Some(happiness) = self.happiness {
+            writeln!(f, "Happiness: {happiness}")?;
+        }
+        if let Some(tera_type) = &self.tera_type {
+            writeln!(f, "Tera Type: {tera_type}")?;
+        }
+        if let Some(evs) = format_spread(&self.evs, DEFAULT_EV) {
+            writeln!(f, "EVs: {evs}")?;
+        }
+        if let Some(nature) = &self.nature {
+            writeln!(f, "{nature} Nature")?;
+        }
+        if let Some(ivs) = format_spread(&self.ivs, DEFAULT_IV) {
+            writeln!(f, "IVs: {ivs}")?;
+        }
+        for pokemon_move in &self.moves {
+            writeln!(f, "- {pokemon_move}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::draft_database::{find_set_folders, DRAFT_SETS_FILE};
+
+    const FULL_SET: &str = "\
+Bolt (Zapdos) (F) @ Heavy-Duty Boots
+Ability: Static
+Level: 50
+Shiny: Yes
+Happiness: 0
+EVs: 248 HP / 8 SpA / 252 Spe
+Timid Nature
+IVs: 0 Atk
+- Discharge
+- Hurricane
+- Roost
+- Volt Switch
+";
+
+    #[test]
+    fn parses_every_part_of_a_set() {
+        let sets = PokemonSet::parse_all(FULL_SET).unwrap();
+        assert_eq!(sets.len(), 1);
+        let set = &sets[0];
+        assert_eq!(set.nickname.as_deref(), Some("Bolt"));
+        assert_eq!(set.species, "Zapdos");
+        assert_eq!(set.gender, Some(Gender::F));
+        assert_eq!(set.item.as_deref(), Some("Heavy-Duty Boots"));
+        assert_eq!(set.ability.as_deref(), Some("Static"));
+        assert_eq!(set.level, 50);
+        assert!(set.shiny);
+        assert_eq!(set.happiness, Some(0));
+        assert_eq!(set.evs, StatSpread { hp: 248, atk: 0, def: 0, spa: 8, spd: 0, spe: 252 });
+        assert_eq!(set.nature.as_deref(), Some("Timid"));
+        assert_eq!(set.ivs, StatSpread { hp: 31, atk: 0, def: 31, spa: 31, spd: 31, spe: 31 });
+        assert_eq!(set.moves, vec!["Discharge", "Hurricane", "Roost", "Volt Switch"]);
+        assert_eq!(set.lines.moves, vec![9, 10, 11, 12]);
+    }
+
+    #[test]
+    fn round_trips_through_display() {
+        let set = &PokemonSet::parse_all(FULL_SET).unwrap()[0];
+        assert_eq!(set.to_string(), FULL_SET);
+    }
+
+    #[test]
+    fn fills_in_missing_optional_fields() {
+        let set = &PokemonSet::parse_all("Snorlax\n- Body Slam\n").unwrap()[0];
+        assert_eq!(set.nickname, None);
+        assert_eq!(set.species, "Snorlax");
+        assert_eq!(set.gender, None);
+        assert_eq!(set.item, None);
+        assert_eq!(set.ability, None);
+        assert_eq!(set.level, DEFAULT_LEVEL);
+        assert_eq!(set.evs, StatSpread::uniform(DEFAULT_EV));
+        assert_eq!(set.ivs, StatSpread::uniform(DEFAULT_IV));
+        assert_eq!(set.tier, None);
+        assert_eq!(set.to_string(), "Snorlax\n- Body Slam\n");
+    }
+
+    #[test]
+    fn keeps_parentheses_in_species_without_a_nickname() {
+        let set = &PokemonSet::parse_all("Rotom-Wash (M) @ Leftovers\n").unwrap()[0];
+        assert_eq!(set.nickname, None);
+        assert_eq!(set.species, "Rotom-Wash");
+        assert_eq!(set.gender, Some(Gender::M));
+    }
+
+    #[test]
+    fn splits_sets_on_blank_lines() {
+        let sets = PokemonSet::parse_all("\nMew\n- Psychic\n\n\nMewtwo\n- Recover\n").unwrap();
+        let species: Vec<&str> = sets.iter().map(|set| set.species.as_str()).collect();
+        assert_eq!(species, vec!["Mew", "Mewtwo"]);
+        assert_eq!(sets[0].line_number, 2);
+        assert_eq!(sets[1].line_number, 6);
+    }
+
+    #[test]
+    fn reports_unknown_attributes_on_their_line() {
+        let error = PokemonSet::parse_all("Mew\n- Psychic\n\nMewtwo\nAbility: Pressure\nMood: Grumpy\n").unwrap_err();
+        assert_eq!(error, SetParseError { line_number: 6, message: "Unrecognised attribute 'Mood'".to_string() });
+    }
+
+    #[test]
+    fn reports_bad_spreads_on_their_line() {
+        let error = PokemonSet::parse_all("Mew\nEVs: 252 Luck\n").unwrap_err();
+        assert_eq!(error, SetParseError { line_number: 2, message: "Unknown stat 'Luck'".to_string() });
+    }
+
+    #[test]
+    fn reports_unrecognised_lines() {
+        let error = PokemonSet::parse_all("Mew\nPsychic\n").unwrap_err();
+        assert_eq!(error.line_number, 2);
+    }
+
+    #[test]
+    fn totals_spreads_too_big_for_a_u16() {
+        let set = &PokemonSet::parse_all("Mew\nEVs: 60000 HP / 60000 Atk\n").unwrap()[0];
+        assert_eq!(set.evs.total(), 120_000);
+    }
+
+    #[test]
+    fn parses_tiers() {
+        let set = &PokemonSet::parse_all("Mew @ Life Orb\nTier: Legendary\n- Psychic\n").unwrap()[0];
+        assert_eq!(set.tier.as_deref(), Some("Legendary"));
+        // Tiers are for collation, not for Showdown
+        assert_eq!(set.to_string(), "Mew @ Life Orb\n- Psychic\n");
+    }
+
+    #[test]
+    fn parses_every_draft_set_in_the_data_folder() {
+        let set_folders = find_set_folders(concat!(env!("CARGO_MANIFEST_DIR"), "/data")).unwrap();
+        assert!(!set_folders.is_empty());
+        for set_folder in set_folders {
+            let path = set_folder.join(DRAFT_SETS_FILE);
+            let text = std::fs::read_to_string(&path).unwrap();
+            let sets = PokemonSet::parse_all(&text).unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+            assert!(!sets.is_empty(), "{} has no sets", path.display());
+        }
+    }
+}
@@ -1,4 +1,6 @@
+use futures_util::{SinkExt, StreamExt};
 use warp::{Filter, Reply};
+use warp::ws::{Message, WebSocket};
 use warp::http::{StatusCode, Uri};
 use std::collections::HashMap;
 use std::future::IntoFuture;
@@ -8,21 +10,26 @@ use std::sync::Arc;
 use handlebars;
 use serde_derive::{Deserialize, Serialize};
 
-use crate::draft_engine::{DraftItemId, GameState, PlayerId};
+use crate::bots::BotStrategy;
+use crate::draft_engine::{DraftError, DraftFormat, DraftItemId, GameState, GridLine, LobbyOptions, PickQueue, PlayerId, UniquenessKey, GRID_WIDTH};
 use crate::lobby_manager::{DraftLobbyId, LobbyManagerRequest, LobbyManagerResponse, LobbyStateForPlayer, LobbyManagerTask};
 
-pub fn make_server_with_tls(configured_addr: SocketAddr,
-                            https_paths: (String, String),
-                            lobby_manager_task_queue: tokio::sync::mpsc::Sender<LobbyManagerTask>,
-                            shutdown_signal: tokio::sync::oneshot::Receiver<()>) -> impl Future<Output=()> {
+/// Every route the web server handles, served the same with or without TLS
+fn routes(admin_token: Option<String>,
+          lobby_manager_task_queue: tokio::sync::mpsc::Sender<LobbyManagerTask>) -> impl Filter<Extract=(impl Reply,), Error=warp::Rejection> + Clone {
     let mspc_tx = warp::any().map(move || lobby_manager_task_queue.clone());
-    let mut handlebars: handlebars::Handlebars = handlebars::Handlebars::new();
+    let mut handlebars = handlebars::Handlebars::new();
+    handlebars.register_template_file("index_template", "www/index_template.html").unwrap();
     handlebars.register_template_file("draft_template", "www/draft_template.html").unwrap();
     handlebars.register_template_file("share_game_template", "www/share_game_template.html").unwrap();
+    handlebars.register_template_file("error_template", "www/error_template.html").unwrap();
     let handlebars = Arc::new(handlebars);
     let handlebars = warp::any().map(move || handlebars.clone());
 
-    let index_route = warp::path::end().and(warp::fs::file("www/static/index.html"));
+    let index_route = warp::path::end()
+        .and(mspc_tx.clone())
+        .and(handlebars.clone())
+        .and_then(get_index_page);
     let static_route = warp::path("static").and(warp::fs::dir("www/static"));
     let draft_route = warp::get()
         .and(mspc_tx.clone())
@@ -35,31 +42,92 @@ pub fn make_server_with_tls(configured_addr: SocketAddr,
         .and(warp::body::content_length_limit(1024 * 16))
         .and(warp::body::json())
         .and_then(handle_draft_post);
+    let draft_ws_route = warp::path!("ws" / "draft" / DraftLobbyId / PlayerId)
+        .and(warp::ws())
+        .and(mspc_tx.clone())
+        .map(|lobby_id, player_id, ws: warp::ws::Ws, mpsc_tx| {
+            ws.on_upgrade(move |socket| push_draft_updates(socket, mpsc_tx, lobby_id, player_id))
+        });
 
     let create_draft_route = warp::get()
         .and(mspc_tx.clone())
         .and(handlebars.clone())
         .and(warp::path!("new_draft" / String ))
+        .and(warp::query::<NewDraftQuery>())
         .and_then(new_draft);
     let join_draft_get_route = warp::get()
         .and(warp::path("join_draft"))
         .and(warp::fs::file("www/join_game_template.html"));
     let join_draft_post_route = warp::post()
         .and(mspc_tx.clone())
+        .and(handlebars.clone())
         .and(warp::path!("join_draft" / DraftLobbyId))
         .and(warp::body::content_length_limit(1024 * 16))
         .and(warp::body::form())
         .and_then(post_playername);
+    let api_sets_route = warp::get()
+        .and(mspc_tx.clone())
+        .and(warp::path!("api" / "sets"))
+        .and_then(get_api_sets);
+    let api_set_items_route = warp::get()
+        .and(mspc_tx.clone())
+        .and(warp::path!("api" / "sets" / String))
+        .and_then(get_api_set_items);
+    let api_draft_events_route = warp::get()
+        .and(mspc_tx.clone())
+        .and(warp::path!("api" / "draft" / DraftLobbyId / PlayerId / "events"))
+        .and_then(get_api_draft_events);
+    let api_draft_state_route = warp::get()
+        .and(mspc_tx.clone())
+        .and(warp::path!("api" / "draft" / DraftLobbyId / PlayerId))
+        .and_then(get_api_draft_state);
+    let api_draft_poll_route = warp::get()
+        .and(mspc_tx.clone())
+        .and(warp::path!("api" / "draft" / DraftLobbyId / PlayerId / "poll"))
+        .and(warp::query::<ApiPollQuery>())
+        .and_then(get_api_draft_poll);
+    let api_draft_start_route = warp::post()
+        .and(mspc_tx.clone())
+        .and(warp::path!("api" / "draft" / DraftLobbyId / PlayerId / "start"))
+        .and_then(post_api_draft_start);
+    let api_draft_pick_route = warp::post()
+        .and(mspc_tx.clone())
+        .and(warp::path!("api" / "draft" / DraftLobbyId / PlayerId / "pick"))
+        .and(warp::body::content_length_limit(1024 * 16))
+        .and(warp::body::json())
+        .and_then(post_api_draft_pick);
+    let reload_sets_route = warp::post()
+        .and(mspc_tx.clone())
+        .and(warp::any().map(move || admin_token.clone()))
+        .and(warp::path!("admin" / "reload_sets"))
+        .and(warp::header::optional::<String>("x-admin-token"))
+        .and_then(reload_sets);
 
-
-    let routes = warp::get()
+    warp::get()
         .and(index_route)
         .or(static_route)
         .or(create_draft_route)
         .or(draft_route)
         .or(draft_route_post)
+        .or(draft_ws_route)
         .or(join_draft_get_route)
-        .or(join_draft_post_route);
+        .or(join_draft_post_route)
+        .or(api_sets_route)
+        .or(api_set_items_route)
+        .or(api_draft_events_route)
+        .or(api_draft_state_route)
+        .or(api_draft_poll_route)
+        .or(api_draft_start_route)
+        .or(api_draft_pick_route)
+        .or(reload_sets_route)
+}
+
+pub fn make_server_with_tls(configured_addr: SocketAddr,
+                            https_paths: (String, String),
+                            admin_token: Option<String>,
+                            lobby_manager_task_queue: tokio::sync::mpsc::Sender<LobbyManagerTask>,
+                            shutdown_signal: tokio::sync::oneshot::Receiver<()>) -> impl Future<Output=()> {
+    let routes = routes(admin_token, lobby_manager_task_queue);
 
     let (cert, key) = https_paths;
     let (_addr, warp_server) = warp::serve(routes)
@@ -79,54 +147,10 @@ pub fn make_https_redirect_server() -> impl Future<Output=()> {
 }
 
 pub fn make_server(configured_addr: SocketAddr,
+                   admin_token: Option<String>,
                    lobby_manager_task_queue: tokio::sync::mpsc::Sender<LobbyManagerTask>,
                    shutdown_signal: tokio::sync::oneshot::Receiver<()>) -> impl Future<Output=()> {
-    let mspc_tx = warp::any().map(move || lobby_manager_task_queue.clone());
-    let mut handlebars = handlebars::Handlebars::new();
-    handlebars.register_template_file("draft_template", "www/draft_template.html").unwrap();
-    handlebars.register_template_file("share_game_template", "www/share_game_template.html").unwrap();
-    let handlebars = Arc::new(handlebars);
-    let handlebars = warp::any().map(move || handlebars.clone());
-
-    let index_route = warp::path::end().and(warp::fs::file("www/static/index.html"));
-    let static_route = warp::path("static").and(warp::fs::dir("www/static"));
-    let draft_route = warp::get()
-        .and(mspc_tx.clone())
-        .and(handlebars.clone())
-        .and(warp::path!("draft" / DraftLobbyId / PlayerId))
-        .and_then(get_draft_page);
-    let draft_route_post = warp::post()
-        .and(mspc_tx.clone())
-        .and(warp::path!("draft" / DraftLobbyId / PlayerId))
-        .and(warp::body::content_length_limit(1024 * 16))
-        .and(warp::body::json())
-        .and_then(handle_draft_post);
-
-    let create_draft_route = warp::get()
-        .and(mspc_tx.clone())
-        .and(handlebars.clone())
-        .and(warp::path!("new_draft" / String ))
-        .and_then(new_draft);
-    let join_draft_get_route = warp::get()
-        .and(warp::path("join_draft"))
-        .and(warp::fs::file("www/join_game_template.html"));
-    let join_draft_post_route = warp::post()
-        .and(mspc_tx.clone())
-        .and(warp::path!("join_draft" / DraftLobbyId))
-        .and(warp::body::content_length_limit(1024 * 16))
-        .and(warp::body::form())
-        .and_then(post_playername);
-
-
-    let routes = warp::get()
-        .and(index_route)
-        .or(static_route)
-        .or(create_draft_route)
-        .or(draft_route)
-        .or(draft_route_post)
-        .or(join_draft_get_route)
-        .or(join_draft_post_route);
-
+    let routes = routes(admin_token, lobby_manager_task_queue);
 
     let (_addr, warp_server) = warp::serve(routes)
         .bind_with_graceful_shutdown(configured_addr, async {
@@ -143,6 +167,305 @@ fn make_new_draft_response(handlebars: Arc<handlebars::Handlebars<'_>>, lobby_id
     warp::reply::html(render).into_response()
 }
 
+async fn get_index_page(mpsc_tx: tokio::sync::mpsc::Sender<LobbyManagerTask>, handlebars: Arc<handlebars::Handlebars<'_>>) -> Result<warp::reply::Response, std::convert::Infallible> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let request = LobbyManagerTask {
+        request: LobbyManagerRequest::ListSets,
+        response_channel: tx,
+    };
+
+    match mpsc_tx.send(request).await {
+        Ok(_) => (),
+        Err(e) => {
+            log::error!("Failed to enqueue task: {e}");
+            return Ok(make_internal_error_page(&handlebars));
+        }
+    };
+
+    let set_list = match rx.into_future().await {
+        Ok(LobbyManagerResponse::SetList(set_list)) => set_list,
+        Ok(_) => {
+            log::error!("Unexpected task response for ListSets");
+            return Ok(make_internal_error_page(&handlebars));
+        }
+        Err(e) => {
+            log::error!("Didn't receive task response: {e}");
+            return Ok(make_internal_error_page(&handlebars));
+        }
+    };
+
+    let mut data = serde_json::Map::new();
+    data.insert("sets".to_string(), handlebars::to_json(set_list));
+
+    let render = handlebars.render("index_template", &data).unwrap();
+    Ok(warp::reply::html(render).into_response())
+}
+
+async fn get_api_sets(mpsc_tx: tokio::sync::mpsc::Sender<LobbyManagerTask>) -> Result<warp::reply::Response, std::convert::Infallible> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let request = LobbyManagerTask {
+        request: LobbyManagerRequest::ListSets,
+        response_channel: tx,
+    };
+
+    match mpsc_tx.send(request).await {
+        Ok(_) => (),
+        Err(e) => {
+            log::error!("Failed to enqueue task: {e}");
+            return Ok(make_internal_error_response());
+        }
+    };
+
+    match rx.into_future().await {
+        Ok(LobbyManagerResponse::SetList(set_list)) => Ok(warp::reply::json(&set_list).into_response()),
+        Ok(_) => {
+            log::error!("Unexpected task response for ListSets");
+            Ok(make_internal_error_response())
+        }
+        Err(e) => {
+            log::error!("Didn't receive task response: {e}");
+            Ok(make_internal_error_response())
+        }
+    }
+}
+
+async fn get_api_set_items(mpsc_tx: tokio::sync::mpsc::Sender<LobbyManagerTask>, set_name: String) -> Result<warp::reply::Response, std::convert::Infallible> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let request = LobbyManagerTask {
+        request: LobbyManagerRequest::GetSetItems { set_name },
+        response_channel: tx,
+    };
+
+    match mpsc_tx.send(request).await {
+        Ok(_) => (),
+        Err(e) => {
+            log::error!("Failed to enqueue task: {e}");
+            return Ok(make_internal_error_response());
+        }
+    };
+
+    match rx.into_future().await {
+        Ok(LobbyManagerResponse::SetItems(items)) => Ok(warp::reply::json(&items).into_response()),
+        Ok(LobbyManagerResponse::LobbyError(e)) => Ok(make_error_response(&e)),
+        Ok(_) => {
+            log::error!("Unexpected task response for GetSetItems");
+            Ok(make_internal_error_response())
+        }
+        Err(e) => {
+            log::error!("Didn't receive task response: {e}");
+            Ok(make_internal_error_response())
+        }
+    }
+}
+
+/// Streams a lobby's events to a player as server-sent events, named after the type of each event, until the draft
+/// finishes. The stream starts with everything that has happened so far.
+async fn get_api_draft_events(mpsc_tx: tokio::sync::mpsc::Sender<LobbyManagerTask>, lobby_id: DraftLobbyId, player_id: PlayerId) -> Result<warp::reply::Response, std::convert::Infallible> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let request = LobbyManagerTask {
+        request: LobbyManagerRequest::StreamEvents { lobby_id, player_id },
+        response_channel: tx,
+    };
+
+    match mpsc_tx.send(request).await {
+        Ok(_) => (),
+        Err(e) => {
+            log::error!("Failed to enqueue task: {e}");
+            return Ok(make_internal_error_response());
+        }
+    };
+
+    match rx.into_future().await {
+        Ok(LobbyManagerResponse::EventStream(events)) => {
+            let events = futures_util::stream::unfold(events, |mut events| async move {
+                let event = events.recv().await?;
+                Some((warp::sse::Event::default().event(event.name()).json_data(&event), events))
+            });
+            Ok(warp::sse::reply(warp::sse::keep_alive().stream(events)).into_response())
+        }
+        Ok(LobbyManagerResponse::LobbyError(e)) => Ok(make_error_response(&e)),
+        Ok(_) => {
+            log::error!("Unexpected task response for StreamEvents");
+            Ok(make_internal_error_response())
+        }
+        Err(e) => {
+            log::error!("Didn't receive task response: {e}");
+            Ok(make_internal_error_response())
+        }
+    }
+}
+
+/// The body of every error response, e.g. `{"error": "lobby_full", "message": "Lobby full"}`
+#[derive(Serialize, Debug)]
+struct ApiError {
+    error: &'static str,
+    message: String,
+}
+
+impl ApiError {
+    fn new(error: &DraftError) -> ApiError {
+        ApiError { error: error.kind(), message: error.to_string() }
+    }
+}
+
+fn status_for(error: &DraftError) -> StatusCode {
+    match error {
+        DraftError::LobbyNotFound | DraftError::UnknownSet(_) | DraftError::PlayerNotFound => StatusCode::NOT_FOUND,
+        // The request made sense, but not in the lobby's current state
+        DraftError::LobbyFull
+        | DraftError::NameTaken(_)
+        | DraftError::AlreadyStarted
+        | DraftError::NotStarted
+        | DraftError::NotYourTurn
+        | DraftError::ItemNotInPack
+        | DraftError::InvalidMove(_) => StatusCode::CONFLICT,
+        DraftError::NotInSet(_) | DraftError::WrongFormat(_) | DraftError::InvalidConfig(_) => StatusCode::BAD_REQUEST,
+        DraftError::NotEnoughItems(_) | DraftError::InvalidSets(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        DraftError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn make_error_response(error: &DraftError) -> warp::reply::Response {
+    warp::reply::with_status(warp::reply::json(&ApiError::new(error)), status_for(error)).into_response()
+}
+
+/// For when the lobby manager can't be reached, or gives a response that doesn't fit the request
+fn make_internal_error_response() -> warp::reply::Response {
+    make_error_response(&DraftError::Internal("Internal error".to_string()))
+}
+
+/// Error responses for the pages people open in a browser, which get the error template rather than JSON
+fn make_error_page(handlebars: &handlebars::Handlebars<'_>, error: &DraftError) -> warp::reply::Response {
+    let mut data = serde_json::Map::new();
+    data.insert("message".to_string(), handlebars::to_json(error.to_string()));
+
+    let render = handlebars.render("error_template", &data).unwrap();
+    warp::reply::with_status(warp::reply::html(render), status_for(error)).into_response()
+}
+
+fn make_internal_error_page(handlebars: &handlebars::Handlebars<'_>) -> warp::reply::Response {
+    make_error_page(handlebars, &DraftError::Internal("Internal error".to_string()))
+}
+
+/// Sends a request to the lobby manager and waits for its response. Failures are logged and returned as API errors.
+async fn request_for_api(mpsc_tx: &tokio::sync::mpsc::Sender<LobbyManagerTask>, request: LobbyManagerRequest) -> Result<LobbyManagerResponse, warp::reply::Response> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    if let Err(e) = mpsc_tx.send(LobbyManagerTask { request, response_channel: tx }).await {
+        log::error!("Failed to enqueue task: {e}");
+        return Err(make_internal_error_response());
+    }
+    rx.into_future().await.map_err(|e| {
+        log::error!("Didn't receive task response: {e}");
+        make_internal_error_response()
+    })
+}
+
+async fn get_api_summary(mpsc_tx: &tokio::sync::mpsc::Sender<LobbyManagerTask>, lobby_id: DraftLobbyId, player_id: PlayerId) -> warp::reply::Response {
+    match request_for_api(mpsc_tx, LobbyManagerRequest::GetLobbySummary { lobby_id, player_id }).await {
+        Ok(LobbyManagerResponse::LobbySummary(summary)) => warp::reply::json(&*summary).into_response(),
+        Ok(LobbyManagerResponse::LobbyError(e)) => make_error_response(&e),
+        Ok(_) => {
+            log::error!("Unexpected task response for GetLobbySummary");
+            make_internal_error_response()
+        }
+        Err(response) => response,
+    }
+}
+
+async fn get_api_draft_state(mpsc_tx: tokio::sync::mpsc::Sender<LobbyManagerTask>, lobby_id: DraftLobbyId, player_id: PlayerId) -> Result<warp::reply::Response, std::convert::Infallible> {
+    Ok(get_api_summary(&mpsc_tx, lobby_id, player_id).await)
+}
+
+/// `api/draft/{lobby}/{player}/poll?game_state=123` waits until the player's game state isn't 123
+#[derive(Deserialize, Debug)]
+struct ApiPollQuery {
+    game_state: GameState,
+}
+
+/// Returns the player's view of the lobby once it has changed from the game state they last saw
+async fn get_api_draft_poll(mpsc_tx: tokio::sync::mpsc::Sender<LobbyManagerTask>, lobby_id: DraftLobbyId, player_id: PlayerId, query: ApiPollQuery) -> Result<warp::reply::Response, std::convert::Infallible> {
+    let request = LobbyManagerRequest::BlockForUpdate { lobby_id, player_id, game_state: query.game_state };
+    match request_for_api(&mpsc_tx, request).await {
+        Ok(LobbyManagerResponse::UpdateReady) => Ok(get_api_summary(&mpsc_tx, lobby_id, player_id).await),
+        Ok(LobbyManagerResponse::LobbyError(e)) => Ok(make_error_response(&e)),
+        Ok(_) => {
+            log::error!("Unexpected task response for BlockForUpdate");
+            Ok(make_internal_error_response())
+        }
+        Err(response) => Ok(response),
+    }
+}
+
+/// Starts the draft, and returns the player's view of it
+async fn post_api_draft_start(mpsc_tx: tokio::sync::mpsc::Sender<LobbyManagerTask>, lobby_id: DraftLobbyId, player_id: PlayerId) -> Result<warp::reply::Response, std::convert::Infallible> {
+    match request_for_api(&mpsc_tx, LobbyManagerRequest::StartLobby { lobby_id }).await {
+        Ok(LobbyManagerResponse::LobbyStarted) => Ok(get_api_summary(&mpsc_tx, lobby_id, player_id).await),
+        Ok(LobbyManagerResponse::LobbyError(e)) => Ok(make_error_response(&e)),
+        Ok(_) => {
+            log::error!("Unexpected task response for StartLobby");
+            Ok(make_internal_error_response())
+        }
+        Err(response) => Ok(response),
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct ApiPick {
+    item_id: DraftItemId,
+}
+
+/// Picks an item, and returns the player's view of the draft afterwards
+async fn post_api_draft_pick(mpsc_tx: tokio::sync::mpsc::Sender<LobbyManagerTask>, lobby_id: DraftLobbyId, player_id: PlayerId, pick: ApiPick) -> Result<warp::reply::Response, std::convert::Infallible> {
+    match request_for_api(&mpsc_tx, LobbyManagerRequest::MakePick { lobby_id, player_id, pick: pick.item_id }).await {
+        Ok(LobbyManagerResponse::PickMade) => Ok(get_api_summary(&mpsc_tx, lobby_id, player_id).await),
+        Ok(LobbyManagerResponse::LobbyError(e)) => Ok(make_error_response(&e)),
+        Ok(_) => {
+            log::error!("Unexpected task response for MakePick");
+            Ok(make_internal_error_response())
+        }
+        Err(response) => Ok(response),
+    }
+}
+
+async fn reload_sets(mpsc_tx: tokio::sync::mpsc::Sender<LobbyManagerTask>, admin_token: Option<String>, provided_token: Option<String>) -> Result<warp::reply::Response, std::convert::Infallible> {
+    // The endpoint doesn't exist unless an admin token is configured
+    let admin_token = match admin_token {
+        Some(admin_token) => admin_token,
+        None => return Ok(StatusCode::NOT_FOUND.into_response()),
+    };
+    if provided_token.as_ref() != Some(&admin_token) {
+        log::warn!("Rejected a set reload with a bad admin token");
+        return Ok(StatusCode::FORBIDDEN.into_response());
+    }
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let request = LobbyManagerTask {
+        request: LobbyManagerRequest::ReloadSets,
+        response_channel: tx,
+    };
+
+    match mpsc_tx.send(request).await {
+        Ok(_) => (),
+        Err(e) => {
+            log::error!("Failed to enqueue task: {e}");
+            return Ok(make_internal_error_response());
+        }
+    };
+
+    match rx.into_future().await {
+        Ok(LobbyManagerResponse::SetsReloaded { num_sets }) => Ok(format!("Reloaded {num_sets} draft sets\n").into_response()),
+        Ok(LobbyManagerResponse::LobbyError(e)) => Ok(make_error_response(&e)),
+        Ok(_) => {
+            log::error!("Unexpected task response for ReloadSets");
+            Ok(make_internal_error_response())
+        }
+        Err(e) => {
+            log::error!("Didn't receive task response: {e}");
+            Ok(make_internal_error_response())
+        }
+    }
+}
+
 fn make_redirect_to_game_response(lobby_id: DraftLobbyId, player_id: PlayerId) -> warp::reply::Response {
     let body = format!(r#"
 <html>
@@ -152,10 +475,45 @@ fn make_redirect_to_game_response(lobby_id: DraftLobbyId, player_id: PlayerId) -
     warp::reply::html(body).into_response()
 }
 
-async fn new_draft(mpsc_tx: tokio::sync::mpsc::Sender<LobbyManagerTask>, handlebars: Arc<handlebars::Handlebars<'_>>, set_name: String) -> Result<warp::reply::Response, std::convert::Infallible> {
+/// `new_draft/{set_name}?seed=123&format=snake&capacity=8&rounds=3&pack_size=10&pick_time_s=20` creates a lobby with a
+/// particular seed and config. Config that isn't given comes from the set. Auction drafts also take a `budget`.
+#[derive(Deserialize, Debug)]
+struct NewDraftQuery {
+    seed: Option<u64>,
+    format: Option<DraftFormat>,
+    capacity: Option<usize>,
+    rounds: Option<usize>,
+    pack_size: Option<usize>,
+    pick_time_s: Option<f64>,
+    budget: Option<u32>,
+    uniqueness: Option<UniquenessKey>,
+    /// Comma separated species, base species or families to leave out of the draft
+    ban: Option<String>,
+}
+
+impl NewDraftQuery {
+    fn lobby_options(&self) -> LobbyOptions {
+        LobbyOptions {
+            format: self.format,
+            capacity: self.capacity,
+            num_rounds: self.rounds,
+            pack_size: self.pack_size,
+            time_per_pack_item_s: self.pick_time_s,
+            auction_budget: self.budget,
+            uniqueness: self.uniqueness,
+            banned: self.ban.iter()
+                .flat_map(|ban| ban.split(','))
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect(),
+        }
+    }
+}
+
+async fn new_draft(mpsc_tx: tokio::sync::mpsc::Sender<LobbyManagerTask>, handlebars: Arc<handlebars::Handlebars<'_>>, set_name: String, query: NewDraftQuery) -> Result<warp::reply::Response, std::convert::Infallible> {
     let (tx, rx) = tokio::sync::oneshot::channel();
     let request = LobbyManagerTask {
-        request: LobbyManagerRequest::CreateLobby{set_name},
+        request: LobbyManagerRequest::CreateLobby { set_name, seed: query.seed, options: query.lobby_options() },
         response_channel: tx,
     };
 
@@ -163,7 +521,7 @@ async fn new_draft(mpsc_tx: tokio::sync::mpsc::Sender<LobbyManagerTask>, handleb
         Ok(_) => (),
         Err(e) => {
             log::error!("Failed to enqueue task: {e}");
-            return Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+            return Ok(make_internal_error_page(&handlebars));
         }
     };
 
@@ -171,24 +529,24 @@ async fn new_draft(mpsc_tx: tokio::sync::mpsc::Sender<LobbyManagerTask>, handleb
 
     match f.await {
         Ok(response) => match response {
-            LobbyManagerResponse::LobbyErrorMsg(e) => {
-                log::warn!("Returning LobbyErrorMsg {e} to end-client");
-                Ok(warp::reply::html(e.to_string()).into_response())
+            LobbyManagerResponse::LobbyError(e) => {
+                log::warn!("Returning {} error to end-client: {e}", e.kind());
+                Ok(make_error_page(&handlebars, &e))
             }
             LobbyManagerResponse::LobbyCreated(id) => Ok(make_new_draft_response(handlebars, id)),
             _ => {
                 log::error!("Unexpected task response for CreateLobby");
-                Ok(warp::reply::html("foo").into_response())
+                Ok(make_internal_error_page(&handlebars))
             }
         },
         Err(e) => {
             log::error!("Didn't receive task response: {e}");
-            Ok(warp::reply::html("foo").into_response())
+            Ok(make_internal_error_page(&handlebars))
         }
     }
 }
 
-async fn post_playername(mpsc_tx: tokio::sync::mpsc::Sender<LobbyManagerTask>, lobby_id: DraftLobbyId, simple_map: HashMap<String, String>) -> Result<warp::reply::Response, std::convert::Infallible> {
+async fn post_playername(mpsc_tx: tokio::sync::mpsc::Sender<LobbyManagerTask>, handlebars: Arc<handlebars::Handlebars<'_>>, lobby_id: DraftLobbyId, simple_map: HashMap<String, String>) -> Result<warp::reply::Response, std::convert::Infallible> {
     let player_name = simple_map.get("player_name").cloned();
     if player_name.is_none() {
         return Ok(StatusCode::BAD_REQUEST.into_response());
@@ -211,26 +569,26 @@ async fn post_playername(mpsc_tx: tokio::sync::mpsc::Sender<LobbyManagerTask>, l
         Ok(_) => (),
         Err(e) => {
             log::error!("Failed to enqueue task: {e}");
-            return Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+            return Ok(make_internal_error_page(&handlebars));
         }
     };
 
     let f = rx.into_future();
     match f.await {
         Ok(response) => match response {
-            LobbyManagerResponse::LobbyErrorMsg(e) => {
-                log::warn!("Returning LobbyErrorMsg {e} to end-client");
-                Ok(warp::reply::html(e.to_string()).into_response())
+            LobbyManagerResponse::LobbyError(e) => {
+                log::warn!("Returning {} error to end-client: {e}", e.kind());
+                Ok(make_error_page(&handlebars, &e))
             }
             LobbyManagerResponse::LobbyJoined { lobby_id, player_id } => Ok(make_redirect_to_game_response(lobby_id, player_id)),
             _ => {
                 log::error!("Unexpected task response for JoinLobby");
-                Ok(warp::reply::html("foo").into_response())
+                Ok(make_internal_error_page(&handlebars))
             }
         },
         Err(e) => {
             log::error!("Didn't receive task response: {e}");
-            Ok(warp::reply::html("foo").into_response())
+            Ok(make_internal_error_page(&handlebars))
         }
     }
 }
@@ -246,7 +604,7 @@ async fn get_draft_page(mpsc_tx: tokio::sync::mpsc::Sender<LobbyManagerTask>, ha
         Ok(_) => (),
         Err(e) => {
             log::error!("Failed to enqueue task: {e}");
-            return Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+            return Ok(make_internal_error_page(&handlebars));
         }
     };
 
@@ -254,19 +612,19 @@ async fn get_draft_page(mpsc_tx: tokio::sync::mpsc::Sender<LobbyManagerTask>, ha
 
     let lobby_state: LobbyStateForPlayer = match f.await {
         Ok(response) => match response {
-            LobbyManagerResponse::LobbyErrorMsg(e) => {
-                log::warn!("Returning LobbyErrorMsg {e} to end-client");
-                return Ok(warp::reply::html(e.to_string()).into_response());
+            LobbyManagerResponse::LobbyError(e) => {
+                log::warn!("Returning {} error to end-client: {e}", e.kind());
+                return Ok(make_error_page(&handlebars, &e));
             }
-            LobbyManagerResponse::LobbyState(state) => state,
+            LobbyManagerResponse::LobbyState(state) => *state,
             _ => {
                 log::error!("Unexpected task response for GetLobbyState");
-                return Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+                return Ok(make_internal_error_page(&handlebars));
             }
         },
         Err(e) => {
             log::error!("Didn't receive task response: {e}");
-            return Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+            return Ok(make_internal_error_page(&handlebars));
         }
     };
 
@@ -286,6 +644,15 @@ async fn get_draft_page(mpsc_tx: tokio::sync::mpsc::Sender<LobbyManagerTask>, ha
 
     let waiting_for_pack: bool = !lobby_state.draft_is_finished && pickable_items.is_empty() && !lobby_state.allocated_picks.is_empty();
 
+    let mut public_pool: Vec<HashMap<String, String>> = vec![];
+    for (draft_item_id, template, stats) in lobby_state.public_pool {
+        let mut temp_map: HashMap<String, String> = HashMap::new();
+        temp_map.insert("pokepaste".to_string(), template);
+        temp_map.insert("pokestats".to_string(), stats);
+        temp_map.insert("draft_id".to_string(), draft_item_id.to_string());
+        public_pool.push(temp_map)
+    }
+
     let mut allocated_items: Vec<HashMap<String, String>> = vec![];
     for (template, stats) in lobby_state.allocated_picks {
         let mut temp_map: HashMap<String, String> = HashMap::new();
@@ -294,15 +661,64 @@ async fn get_draft_page(mpsc_tx: tokio::sync::mpsc::Sender<LobbyManagerTask>, ha
         allocated_items.push(temp_map)
     }
 
+    let current_bid = lobby_state.current_bid.map(|bid| {
+        let mut bid_data = serde_json::Map::new();
+        bid_data.insert("pokepaste".to_string(), handlebars::to_json(bid.template));
+        bid_data.insert("pokestats".to_string(), handlebars::to_json(bid.stats));
+        bid_data.insert("high_bidder".to_string(), handlebars::to_json(bid.high_bidder));
+        bid_data.insert("amount".to_string(), handlebars::to_json(bid.amount));
+        bid_data.insert("min_bid".to_string(), handlebars::to_json(bid.amount + 1));
+        bid_data.insert("max_bid".to_string(), handlebars::to_json(bid.max_bid));
+        bid_data
+    });
+    let budgets: Vec<HashMap<String, String>> = lobby_state.budgets.into_iter()
+        .map(|(name, budget)| HashMap::from([("name".to_string(), name), ("budget".to_string(), budget.to_string())]))
+        .collect();
+    let is_auction = !budgets.is_empty();
+    let is_winston = lobby_state.winston_piles.is_some();
+    let timer_label = match (current_bid.is_some(), is_auction, is_winston) {
+        (true, _, _) => "Sold in: ",
+        (false, true, _) => "Auto-nominate in: ",
+        (false, false, true) => "Pile taken in: ",
+        (false, false, false) => "Auto-pick in: ",
+    };
+    let grid_rows: Vec<serde_json::Value> = lobby_state.grid.chunks(GRID_WIDTH).enumerate()
+        .map(|(row, cells)| serde_json::json!({
+            "row": row,
+            "cells": cells.iter()
+                .map(|cell| cell.as_ref().map(|(template, stats)| serde_json::json!({"pokepaste": template, "pokestats": stats})))
+                .collect::<Vec<_>>(),
+        }))
+        .collect();
+    let grid_columns: Vec<usize> = (0..GRID_WIDTH).collect();
+    let show_timer = !pickable_items.is_empty() || current_bid.is_some() || lobby_state.can_take_line;
+    let waiting_for_pack = waiting_for_pack && current_bid.is_none() && !lobby_state.can_take_line;
+
     let (current_round, total_rounds, current_pick, pack_size) = &lobby_state.rounds_and_picks;
 
     data.insert("lobby_id".to_string(), handlebars::to_json(&lobby_state.lobby_id));
     data.insert("player_id".to_string(), handlebars::to_json(&lobby_state.player_id));
     data.insert("joining_players".to_string(), handlebars::to_json(&lobby_state.joining_players));
     data.insert("open_slots".to_string(), handlebars::to_json(&lobby_state.open_slots));
+    data.insert("can_add_bots".to_string(), handlebars::to_json(lobby_state.can_add_bots));
     data.insert("pending_picks".to_string(), handlebars::to_json(&pickable_items));
     data.insert("allocated_picks".to_string(), handlebars::to_json(&allocated_items));
+    data.insert("public_pool".to_string(), handlebars::to_json(&public_pool));
+    data.insert("pick_queue".to_string(), handlebars::to_json(&lobby_state.pick_queue));
+    data.insert("pick_queue_names".to_string(), handlebars::to_json(&lobby_state.pick_queue_names));
+    data.insert("pick_queue_json".to_string(), handlebars::to_json(serde_json::to_string(&lobby_state.pick_queue).unwrap()));
+    data.insert("current_turn".to_string(), handlebars::to_json(&lobby_state.current_turn));
+    data.insert("winston_piles".to_string(), handlebars::to_json(&lobby_state.winston_piles));
+    data.insert("grid_rows".to_string(), handlebars::to_json(&grid_rows));
+    data.insert("grid_columns".to_string(), handlebars::to_json(&grid_columns));
+    data.insert("can_take_line".to_string(), handlebars::to_json(lobby_state.can_take_line));
+    data.insert("is_auction".to_string(), handlebars::to_json(is_auction));
+    data.insert("current_bid".to_string(), handlebars::to_json(&current_bid));
+    data.insert("budgets".to_string(), handlebars::to_json(&budgets));
+    data.insert("show_timer".to_string(), handlebars::to_json(show_timer));
+    data.insert("timer_label".to_string(), handlebars::to_json(timer_label));
     data.insert("game_state".to_string(), handlebars::to_json(&lobby_state.game_state));
+    data.insert("seed".to_string(), handlebars::to_json(lobby_state.seed));
     data.insert("waiting_for_pack".to_string(), handlebars::to_json(waiting_for_pack));
     data.insert("time_left_s".to_string(), handlebars::to_json(&lobby_state.time_to_pick_s));
     data.insert("draft_order".to_string(), handlebars::to_json(&lobby_state.draft_order));
@@ -324,13 +740,37 @@ struct DraftPost {
     player_id: PlayerId,
     pick_id: DraftItemId,
     game_state: GameState,
+    #[serde(default)]
+    bid: u32,
+    #[serde(default)]
+    line: Option<GridLine>,
+    #[serde(default)]
+    strategy: Option<BotStrategy>,
+    #[serde(default)]
+    pick_queue: Option<PickQueue>,
 }
 
 async fn handle_draft_post(mpsc_tx: tokio::sync::mpsc::Sender<LobbyManagerTask>, lobby_id: DraftLobbyId, player_id: PlayerId, post_data: DraftPost) -> Result<impl warp::Reply, std::convert::Infallible> {
     let (tx, rx) = tokio::sync::oneshot::channel();
     let request = match post_data.command.as_str() {
         "start_game" => LobbyManagerRequest::StartLobby { lobby_id },
+        "add_bot" => match post_data.strategy {
+            Some(strategy) => LobbyManagerRequest::AddBot { lobby_id, strategy },
+            None => return Ok(StatusCode::BAD_REQUEST.into_response()),
+        },
         "pick" => LobbyManagerRequest::MakePick { lobby_id, player_id, pick: post_data.pick_id },
+        "nominate" => LobbyManagerRequest::Nominate { lobby_id, player_id, pick: post_data.pick_id },
+        "bid" => LobbyManagerRequest::Bid { lobby_id, player_id, amount: post_data.bid },
+        "take_pile" => LobbyManagerRequest::TakePile { lobby_id, player_id },
+        "pass_pile" => LobbyManagerRequest::PassPile { lobby_id, player_id },
+        "take_line" => match post_data.line {
+            Some(line) => LobbyManagerRequest::TakeLine { lobby_id, player_id, line },
+            None => return Ok(StatusCode::BAD_REQUEST.into_response()),
+        },
+        "set_pick_queue" => match post_data.pick_queue {
+            Some(queue) => LobbyManagerRequest::SetPickQueue { lobby_id, player_id, queue },
+            None => return Ok(StatusCode::BAD_REQUEST.into_response()),
+        },
         "poll" => LobbyManagerRequest::BlockForUpdate { lobby_id, player_id, game_state: post_data.game_state },
         _ => return Ok(StatusCode::BAD_REQUEST.into_response()),
     };
@@ -340,28 +780,203 @@ async fn handle_draft_post(mpsc_tx: tokio::sync::mpsc::Sender<LobbyManagerTask>,
         Ok(_) => (),
         Err(e) => {
             log::error!("Failed to enqueue task: {e}");
-            return Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+            return Ok(make_internal_error_response());
         }
     };
 
     let f = rx.into_future();
     match f.await {
         Ok(response) => match response {
-            LobbyManagerResponse::LobbyErrorMsg(e) => {
-                log::warn!("Returning LobbyErrorMsg {e} to end-client");
-                Ok(warp::reply::html(e.to_string()).into_response())
+            LobbyManagerResponse::LobbyError(e) => {
+                log::warn!("Returning {} error to end-client: {e}", e.kind());
+                Ok(make_error_response(&e))
             }
             LobbyManagerResponse::LobbyStarted => Ok(StatusCode::OK.into_response()),
+            LobbyManagerResponse::BotAdded => Ok(StatusCode::OK.into_response()),
             LobbyManagerResponse::PickMade => Ok(StatusCode::OK.into_response()),
+            LobbyManagerResponse::Nominated => Ok(StatusCode::OK.into_response()),
+            LobbyManagerResponse::BidPlaced => Ok(StatusCode::OK.into_response()),
+            LobbyManagerResponse::PileTaken => Ok(StatusCode::OK.into_response()),
+            LobbyManagerResponse::PilePassed => Ok(StatusCode::OK.into_response()),
+            LobbyManagerResponse::LineTaken => Ok(StatusCode::OK.into_response()),
+            LobbyManagerResponse::PickQueueSet => Ok(StatusCode::OK.into_response()),
             LobbyManagerResponse::UpdateReady => Ok(StatusCode::OK.into_response()),
             _ => {
                 log::error!("Unexpected task response");
-                Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response())
+                Ok(make_internal_error_response())
             }
         },
         Err(e) => {
             log::error!("Didn't receive task response: {e}");
-            Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response())
+            Ok(make_internal_error_response())
+        }
+    }
+}
+
+/// Pushes a player's view of a lobby over a websocket, as the JSON of their `LobbyStateForPlayer`. The first message
+/// has every field, and each message after that has only the fields that changed, so the page can update in place.
+/// Updates come from the same listeners as the `poll` command, and the socket closes when the draft finishes.
+/// How long a WebSocket client has to take an update before it's disconnected
+const WS_SEND_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// The top-level fields of `state` that weren't last sent with the same value
+fn changed_fields(last_sent: &serde_json::Map<String, serde_json::Value>, state: &serde_json::Map<String, serde_json::Value>) -> serde_json::Map<String, serde_json::Value> {
+    state.iter()
+        .filter(|(field, value)| last_sent.get(*field) != Some(value))
+        .map(|(field, value)| (field.clone(), value.clone()))
+        .collect()
+}
+
+async fn push_draft_updates(socket: WebSocket, mpsc_tx: tokio::sync::mpsc::Sender<LobbyManagerTask>, lobby_id: DraftLobbyId, player_id: PlayerId) {
+    let (mut ws_tx, mut ws_rx) = socket.split();
+    let mut last_sent = serde_json::Map::new();
+    loop {
+        let lobby_state = match get_lobby_state(&mpsc_tx, lobby_id, player_id).await {
+            Ok(lobby_state) => lobby_state,
+            Err(e) => {
+                let error = serde_json::to_string(&ApiError::new(&e)).unwrap();
+                ws_tx.send(Message::text(error)).await.ok();
+                break;
+            }
+        };
+        let game_state = lobby_state.game_state;
+        let draft_is_finished = lobby_state.draft_is_finished;
+        let state = match serde_json::to_value(&lobby_state) {
+            Ok(serde_json::Value::Object(state)) => state,
+            _ => {
+                log::error!("Couldn't serialize the state of lobby {lobby_id}");
+                break;
+            }
+        };
+        let diff = changed_fields(&last_sent, &state);
+        // Listeners for people who aren't in the lobby return straight away, so nothing will ever change for them
+        if diff.is_empty() {
+            break;
+        }
+        // A client that stops reading would otherwise hold this task, and its place in the lobby's listeners, forever
+        let message = Message::text(serde_json::Value::Object(diff).to_string());
+        match tokio::time::timeout(WS_SEND_TIMEOUT, ws_tx.send(message)).await {
+            Ok(Ok(())) => (),
+            Ok(Err(_)) => return,
+            Err(_) => {
+                log::info!("Dropping a lagging listener for lobby {lobby_id}");
+                return;
+            }
+        }
+        last_sent = state;
+        if draft_is_finished {
+            break;
+        }
+
+        let (tx, mut rx) = tokio::sync::oneshot::channel();
+        let task = LobbyManagerTask {
+            request: LobbyManagerRequest::BlockForUpdate { lobby_id, player_id, game_state },
+            response_channel: tx,
+        };
+        if let Err(e) = mpsc_tx.send(task).await {
+            log::error!("Failed to enqueue task: {e}");
+            break;
+        }
+        let update = loop {
+            tokio::select! {
+                update = &mut rx => break update,
+                message = ws_rx.next() => match message {
+                    // Commands are still POSTed, so anything the client sends is ignored
+                    Some(Ok(message)) if !message.is_close() => continue,
+                    _ => return,
+                },
+            }
+        };
+        match update {
+            Ok(LobbyManagerResponse::UpdateReady) => (),
+            Ok(LobbyManagerResponse::LobbyError(e)) => {
+                let error = serde_json::to_string(&ApiError::new(&e)).unwrap();
+                ws_tx.send(Message::text(error)).await.ok();
+                break;
+            }
+            Ok(_) => {
+                log::error!("Unexpected task response for BlockForUpdate");
+                break;
+            }
+            Err(e) => {
+                log::error!("Didn't receive task response: {e}");
+                break;
+            }
+        }
+    }
+    ws_tx.close().await.ok();
+}
+
+async fn get_lobby_state(mpsc_tx: &tokio::sync::mpsc::Sender<LobbyManagerTask>, lobby_id: DraftLobbyId, player_id: PlayerId) -> Result<LobbyStateForPlayer, DraftError> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let request = LobbyManagerTask {
+        request: LobbyManagerRequest::GetLobbyState { lobby_id, player_id },
+        response_channel: tx,
+    };
+
+    if let Err(e) = mpsc_tx.send(request).await {
+        log::error!("Failed to enqueue task: {e}");
+        return Err(DraftError::Internal("Internal error".to_string()));
+    }
+
+    match rx.into_future().await {
+        Ok(LobbyManagerResponse::LobbyState(state)) => Ok(*state),
+        Ok(LobbyManagerResponse::LobbyError(e)) => Err(e),
+        Ok(_) => {
+            log::error!("Unexpected task response for GetLobbyState");
+            Err(DraftError::Internal("Internal error".to_string()))
+        }
+        Err(e) => {
+            log::error!("Didn't receive task response: {e}");
+            Err(DraftError::Internal("Internal error".to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn fields(value: serde_json::Value) -> serde_json::Map<String, serde_json::Value> {
+        match value {
+            serde_json::Value::Object(map) => map,
+            _ => panic!("Not an object: {value}"),
+        }
+    }
+
+    #[test]
+    fn sends_every_field_the_first_time() {
+        let state = fields(json!({"game_state": 1, "players": ["Alice"]}));
+        assert_eq!(changed_fields(&serde_json::Map::new(), &state), state);
+    }
+
+    #[test]
+    fn only_sends_the_fields_that_changed() {
+        let last_sent = fields(json!({"game_state": 1, "players": ["Alice"], "pending_picks": [[1, "Garchomp"]]}));
+        let state = fields(json!({"game_state": 2, "players": ["Alice"], "pending_picks": [[1, "Garchomp"], [2, "Blissey"]]}));
+        assert_eq!(changed_fields(&last_sent, &state),
+                   fields(json!({"game_state": 2, "pending_picks": [[1, "Garchomp"], [2, "Blissey"]]})));
+        assert!(changed_fields(&state, &state).is_empty());
+    }
+
+    #[test]
+    fn errors_map_to_statuses_by_kind() {
+        let reason = || "reason".to_string();
+        for error in [DraftError::LobbyNotFound, DraftError::UnknownSet(reason()), DraftError::PlayerNotFound] {
+            assert_eq!(status_for(&error), StatusCode::NOT_FOUND, "{error:?}");
+        }
+        for error in [DraftError::LobbyFull, DraftError::NameTaken(reason()), DraftError::AlreadyStarted,
+                      DraftError::NotStarted, DraftError::NotYourTurn, DraftError::ItemNotInPack,
+                      DraftError::InvalidMove(reason())] {
+            assert_eq!(status_for(&error), StatusCode::CONFLICT, "{error:?}");
+        }
+        for error in [DraftError::NotInSet(reason()), DraftError::WrongFormat(reason()), DraftError::InvalidConfig(reason())] {
+            assert_eq!(status_for(&error), StatusCode::BAD_REQUEST, "{error:?}");
+        }
+        for error in [DraftError::NotEnoughItems(reason()), DraftError::InvalidSets(reason())] {
+            assert_eq!(status_for(&error), StatusCode::UNPROCESSABLE_ENTITY, "{error:?}");
         }
+        assert_eq!(status_for(&DraftError::Internal(reason())), StatusCode::INTERNAL_SERVER_ERROR);
     }
 }
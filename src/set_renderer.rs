@@ -0,0 +1,123 @@
+use std::fmt::Write;
+
+use crate::dex::Dex;
+use crate::pokemon_set::{ALL_STATS, DEFAULT_EV, DEFAULT_IV, DEFAULT_LEVEL, PokemonSet, Stat, StatSpread};
+use crate::stats;
+
+const ASSET_PATH: &str = "/static/assets";
+
+/// Renders a set the same way PokePaste does, using the classes defined in `draft.css`.
+pub fn render_pokepaste(set: &PokemonSet, dex: &Dex) -> String {
+    let mut html = String::new();
+    let species_data = dex.get_species(&set.species);
+
+    html.push_str("\n<div class=\"img\">\n");
+    if let Some(species_data) = species_data {
+        writeln!(html, "<img class=\"img-pokemon\" src=\"{ASSET_PATH}/{}.png\"/>", species_data.sprite).unwrap();
+    }
+    if let Some(item_data) = set.item.as_ref().and_then(|item| dex.get_item(item)) {
+        writeln!(html, "<img class=\"img-item\" src=\"{ASSET_PATH}/{}.png\"/>", item_data.sprite).unwrap();
+    }
+    html.push_str("</div>\n<pre>");
+
+    let species_type = species_data.and_then(|s| s.types.first());
+    let species = typed_span(species_type, &set.species);
+    match &set.nickname {
+        Some(nickname) => write!(html, "{} ({species})", escape_html(nickname)).unwrap(),
+        None => html.push_str(&species),
+    }
+    if let Some(gender) = &set.gender {
+        write!(html, " (<span class=\"gender-{}\">{gender}</span>)", gender.to_string().to_lowercase()).unwrap();
+    }
+    if let Some(item) = &set.item {
+        let item_type = dex.get_item(item).and_then(|i| i.item_type.as_ref());
+        write!(html, " @ {}", typed_span(item_type, item)).unwrap();
+    }
+    html.push('\n');
+
+    if let Some(ability) = &set.ability {
+        writeln!(html, "{}{}", attr("Ability"), escape_html(ability)).unwrap();
+    }
+    if set.level != DEFAULT_LEVEL {
+        writeln!(html, "{}{}", attr("Level"), set.level).unwrap();
+    }
+    if set.shiny {
+        writeln!(html, "{}Yes", attr("Shiny")).unwrap();
+    }
+    if let Some(happiness) = set.happiness {
+        writeln!(html, "{}{happiness}", attr("Happiness")).unwrap();
+    }
+    if let Some(tera_type) = &set.tera_type {
+        writeln!(html, "{}{}", attr("Tera Type"), typed_span(Some(tera_type), tera_type)).unwrap();
+    }
+    if let Some(evs) = render_spread(&set.evs, DEFAULT_EV) {
+        writeln!(html, "{}{evs}", attr("EVs")).unwrap();
+    }
+    if let Some(nature) = &set.nature {
+        writeln!(html, "{} Nature", escape_html(nature)).unwrap();
+    }
+    if let Some(ivs) = render_spread(&set.ivs, DEFAULT_IV) {
+        writeln!(html, "{}{ivs}", attr("IVs")).unwrap();
+    }
+    for pokemon_move in &set.moves {
+        let move_type = dex.get_move(pokemon_move).map(|m| &m.move_type);
+        writeln!(html, "{} {}", typed_span(move_type, "-"), escape_html(pokemon_move)).unwrap();
+    }
+    html.push_str("\n</pre>\n");
+    html
+}
+
+/// Renders stat bars the same way the Showdown teambuilder does, along with each final stat value.
+/// Returns an empty string for species that aren't in the dex.
+pub fn render_stat_bars(set: &PokemonSet, dex: &Dex) -> String {
+    let species_data = match dex.get_species(&set.species) {
+        Some(species_data) => species_data,
+        None => return String::new(),
+    };
+    let final_stats = stats::compute_stats(&species_data.base_stats, set);
+
+    let mut html = String::new();
+    for stat in ALL_STATS {
+        let value = final_stats.get(stat);
+        let max_value = match stat {
+            Stat::Hp => 704.0,
+            _ => 504.0,
+        };
+        let width = (value as f64 * 75.0 / max_value).min(75.0);
+        let hue = (value as u32 * 180 / 714).min(360);
+        write!(html, "<span class=\"statrow\"><label>{}</label> <span class=\"statgraph\">\
+                      <span style=\"width:{width}px;background:hsl({hue},40%,75%);\"></span></span> \
+                      <em>{value}</em></span>", stat.label()).unwrap();
+    }
+    html
+}
+
+fn attr(name: &str) -> String {
+    format!("<span class=\"attr\">{name}: </span>")
+}
+
+fn typed_span(pokemon_type: Option<&String>, text: &str) -> String {
+    match pokemon_type {
+        Some(t) => format!("<span class=\"type-{}\">{}</span>", t.to_lowercase(), escape_html(text)),
+        None => escape_html(text),
+    }
+}
+
+fn render_spread(spread: &StatSpread, default: u16) -> Option<String> {
+    let entries: Vec<String> = ALL_STATS.iter()
+        .filter(|&&stat| spread.get(stat) != default)
+        .map(|&stat| format!("<span class=\"stat-{}\">{} {}</span>",
+                             stat.label().to_lowercase(), spread.get(stat), stat.label()))
+        .collect();
+    match entries.is_empty() {
+        true => None,
+        false => Some(entries.join(" / ")),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
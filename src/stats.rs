@@ -0,0 +1,96 @@
+use crate::pokemon_set::{ALL_STATS, PokemonSet, Stat, StatSpread};
+
+/// Every nature, with the stat it raises and the stat it lowers. Neutral natures raise and lower nothing.
+pub const NATURES: [(&str, Option<Stat>, Option<Stat>); 25] = [
+    ("Hardy", None, None),
+    ("Lonely", Some(Stat::Atk), Some(Stat::Def)),
+    ("Brave", Some(Stat::Atk), Some(Stat::Spe)),
+    ("Adamant", Some(Stat::Atk), Some(Stat::SpA)),
+    ("Naughty", Some(Stat::Atk), Some(Stat::SpD)),
+    ("Bold", Some(Stat::Def), Some(Stat::Atk)),
+    ("Docile", None, None),
+    ("Relaxed", Some(Stat::Def), Some(Stat::Spe)),
+    ("Impish", Some(Stat::Def), Some(Stat::SpA)),
+    ("Lax", Some(Stat::Def), Some(Stat::SpD)),
+    ("Timid", Some(Stat::Spe), Some(Stat::Atk)),
+    ("Hasty", Some(Stat::Spe), Some(Stat::Def)),
+    ("Serious", None, None),
+    ("Jolly", Some(Stat::Spe), Some(Stat::SpA)),
+    ("Naive", Some(Stat::Spe), Some(Stat::SpD)),
+    ("Modest", Some(Stat::SpA), Some(Stat::Atk)),
+    ("Mild", Some(Stat::SpA), Some(Stat::Def)),
+    ("Quiet", Some(Stat::SpA), Some(Stat::Spe)),
+    ("Bashful", None, None),
+    ("Rash", Some(Stat::SpA), Some(Stat::SpD)),
+    ("Calm", Some(Stat::SpD), Some(Stat::Atk)),
+    ("Gentle", Some(Stat::SpD), Some(Stat::Def)),
+    ("Sassy", Some(Stat::SpD), Some(Stat::Spe)),
+    ("Careful", Some(Stat::SpD), Some(Stat::SpA)),
+    ("Quirky", None, None),
+];
+
+fn nature_multiplier_percent(nature: Option<&String>, stat: Stat) -> u32 {
+    let entry = nature.and_then(|name| NATURES.iter().find(|(n, _, _)| n == name));
+    match entry {
+        Some((_, Some(raised), _)) if *raised == stat => 110,
+        Some((_, _, Some(lowered))) if *lowered == stat => 90,
+        _ => 100,
+    }
+}
+
+/// Sets' final stats are shown at this level, whatever level they're played at, so that sets compare on one scale
+pub const STATS_LEVEL: u32 = 100;
+
+/// Computes a set's final stats at `STATS_LEVEL` from its species' base stats, using the set's EVs, IVs and nature
+pub fn compute_stats(base_stats: &StatSpread, set: &PokemonSet) -> StatSpread {
+    let level = STATS_LEVEL;
+    let mut stats = StatSpread::uniform(0);
+    for stat in ALL_STATS {
+        let base = base_stats.get(stat) as u32;
+        let iv = set.ivs.get(stat) as u32;
+        let ev = set.evs.get(stat) as u32;
+        let scaled = (2 * base + iv + ev / 4) * level / 100;
+        let value = match stat {
+            Stat::Hp => scaled + level + 10,
+            _ => (scaled + 5) * nature_multiplier_percent(set.nature.as_ref(), stat) / 100,
+        };
+        stats.set(stat, value as u16);
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_set(text: &str) -> PokemonSet {
+        PokemonSet::parse_all(text).unwrap().remove(0)
+    }
+
+    fn garchomp() -> StatSpread {
+        StatSpread { hp: 108, atk: 130, def: 95, spa: 80, spd: 85, spe: 102 }
+    }
+
+    #[test]
+    fn computes_level_100_stats_with_evs_and_nature() {
+        let set = parse_set("Garchomp\nEVs: 252 Atk / 4 SpD / 252 Spe\nAdamant Nature\n- Earthquake\n");
+        let stats = compute_stats(&garchomp(), &set);
+        assert_eq!(stats, StatSpread { hp: 357, atk: 394, def: 226, spa: 176, spd: 207, spe: 303 });
+    }
+
+    #[test]
+    fn lowers_the_stat_a_nature_lowers() {
+        let set = parse_set("Garchomp\nTimid Nature\nIVs: 0 Atk\n- Draco Meteor\n");
+        let stats = compute_stats(&garchomp(), &set);
+        // (2 * 130 + 5) * 0.9 and (2 * 102 + 31 + 5) * 1.1, rounded down
+        assert_eq!(stats.atk, 238);
+        assert_eq!(stats.spe, 264);
+    }
+
+    #[test]
+    fn ignores_the_level_a_set_is_played_at() {
+        let level_50 = parse_set("Blissey\nLevel: 50\nEVs: 252 HP\n- Soft-Boiled\n");
+        let blissey = StatSpread { hp: 255, atk: 10, def: 10, spa: 75, spd: 135, spe: 55 };
+        assert_eq!(compute_stats(&blissey, &level_50).hp, 714);
+    }
+}
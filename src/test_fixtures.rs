@@ -0,0 +1,101 @@
+//! Lobbies, draft sets and data folders shared by the tests of the draft engine, the draft database, the lobby manager and the bots
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::dex::{DEX_FOLDER, Dex};
+use crate::draft_database::{DRAFT_SETS_FILE, DraftSet, SET_MANIFEST_FILE};
+use crate::draft_engine::{DraftItemId, DraftLobby, LobbyConfig, PackContents, PlayerId};
+
+pub const DATA_FOLDER: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/data");
+
+pub const PLAYERS: [PlayerId; 3] = [11, 22, 33];
+
+/// A lobby with the given players joined in order
+pub fn make_lobby(config: LobbyConfig, seed: u64, players: &[PlayerId]) -> DraftLobby {
+    let mut lobby = DraftLobby::new("test".to_string(), config, seed);
+    for &player_id in players {
+        lobby.add_player_with_id(player_id, format!("Player {player_id}")).unwrap();
+    }
+    lobby
+}
+
+/// Deals packs from `items` and starts the draft, returning the packs
+pub fn start_lobby(lobby: &mut DraftLobby, items: &Vec<DraftItemId>) -> Vec<PackContents> {
+    let (turn_order, packs) = lobby.deal_packs(items, &[]).unwrap();
+    assert_eq!(&turn_order, lobby.get_player_ids());
+    lobby.start(turn_order, packs.clone()).unwrap();
+    packs
+}
+
+/// Starts a lobby of the given players, dealt from items numbered from 1
+pub fn start_draft(config: LobbyConfig, players: &[PlayerId]) -> DraftLobby {
+    let mut lobby = make_lobby(LobbyConfig { capacity: players.len(), ..config }, 1, players);
+    start_lobby(&mut lobby, &(1..=100).collect());
+    lobby
+}
+
+pub fn allocated_items(lobby: &DraftLobby, player_id: PlayerId) -> Vec<DraftItemId> {
+    lobby.get_player_draft_state(&player_id).unwrap().allocated_items.clone()
+}
+
+pub fn load_dex() -> Dex {
+    Dex::from_folder(&Path::new(DATA_FOLDER).join(DEX_FOLDER)).unwrap()
+}
+
+/// An empty folder in the temp directory, unique to this test run
+pub fn temp_folder(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pkmn-draft-{}-{name}", std::process::id()));
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// A data folder in the temp directory with a copy of the dex, and no sets yet
+pub fn temp_data_folder(name: &str) -> PathBuf {
+    let data_dir = temp_folder(name);
+    let dex_dir = data_dir.join(DEX_FOLDER);
+    fs::create_dir(&dex_dir).unwrap();
+    for entry in fs::read_dir(Path::new(DATA_FOLDER).join(DEX_FOLDER)).unwrap() {
+        let entry = entry.unwrap();
+        fs::copy(entry.path(), dex_dir.join(entry.file_name())).unwrap();
+    }
+    data_dir
+}
+
+/// Writes a set folder with the given `draft_sets.txt`, and a `set.json` if there is one
+pub fn write_set(set_dir: &Path, draft_sets: &str, manifest: Option<&str>) {
+    fs::create_dir_all(set_dir).unwrap();
+    fs::write(set_dir.join(DRAFT_SETS_FILE), draft_sets).unwrap();
+    if let Some(manifest) = manifest {
+        fs::write(set_dir.join(SET_MANIFEST_FILE), manifest).unwrap();
+    }
+}
+
+/// One Earthquake set for each species, in order, so that item ids follow the order of `species`
+pub fn sets_of(species: &[&str]) -> String {
+    species.iter().map(|species| format!("{species}\n- Earthquake\n")).collect::<Vec<_>>().join("\n")
+}
+
+/// Loads a set from the given `draft_sets.txt` and `set.json`
+pub fn load_set(name: &str, draft_sets: &str, manifest: Option<&str>) -> DraftSet {
+    let set_dir = temp_folder(name);
+    write_set(&set_dir, draft_sets, manifest);
+    DraftSet::from_folder(name, &set_dir, &load_dex()).unwrap()
+}
+
+/// Every pack gets one item of tier S and two of tier A, see `tiered_sets`
+pub const TIERED_MANIFEST: &str = r#"{
+    "display_name": "Tiered",
+    "tiers": {"Scizor": "A", "Ferrothorn": "A", "Toxapex": "A", "Alakazam": "A", "Gengar": "A"},
+    "pack_collation": [{"tier": "S", "count": 1}, {"tier": "A", "count": 2}]
+}"#;
+
+/// Three species of tier S, from `Tier:` lines, five of tier A, from `TIERED_MANIFEST`, and six without a tier. One
+/// of the two Garchomp sets has no tier, so Garchomp can't fill a tier S slot.
+pub fn tiered_sets() -> String {
+    let tier_s = ["Garchomp", "Dragonite", "Tyranitar"].map(|species| format!("{species}\nTier: S\n- Earthquake\n"));
+    let others = sets_of(&["Garchomp", "Scizor", "Ferrothorn", "Toxapex", "Alakazam", "Gengar",
+                           "Lucario", "Blissey", "Charizard", "Vaporeon", "Jolteon", "Raichu"]);
+    format!("{}\n{others}", tier_s.join("\n"))
+}
@@ -0,0 +1,192 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::dex::{DEX_FOLDER, Dex};
+use crate::draft_database::{DRAFT_SETS_FILE, find_set_folders};
+use crate::pokemon_set::{ALL_STATS, PokemonSet};
+use crate::stats::NATURES;
+
+pub const MAX_MOVES: usize = 4;
+pub const MAX_EVS_PER_STAT: u16 = 252;
+pub const MAX_EVS_TOTAL: u32 = 510;
+pub const MAX_IV: u16 = 31;
+
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub line_number: usize,
+    pub message: String,
+}
+
+/// Checks a parsed set against the legality data in the dex
+pub fn validate_set(set: &PokemonSet, dex: &Dex) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    let mut report = |line_number: Option<usize>, message: String| diagnostics.push(Diagnostic {
+        line_number: line_number.unwrap_or(set.line_number),
+        message,
+    });
+
+    match dex.get_species(&set.species) {
+        None => report(None, format!("Unknown species '{}'", set.species)),
+        Some(species_data) => {
+            if let Some(ability) = &set.ability {
+                if !species_data.abilities.contains(ability) {
+                    report(set.lines.ability, format!("{} can't have the ability '{ability}'", set.species));
+                }
+            }
+        }
+    }
+    if let Some(item) = &set.item {
+        if dex.get_item(item).is_none() {
+            report(None, format!("Unknown item '{item}'"));
+        }
+    }
+    if let Some(nature) = &set.nature {
+        if !NATURES.iter().any(|(name, _, _)| name == nature) {
+            report(set.lines.nature, format!("Unknown nature '{nature}'"));
+        }
+    }
+    if set.level == 0 || set.level > 100 {
+        report(set.lines.level, format!("Level {} is out of range", set.level));
+    }
+    if set.tera_type.is_some() {
+        report(set.lines.tera_type, "Tera Types aren't used in drafts, remove this line".to_string());
+    }
+
+    for stat in ALL_STATS {
+        if set.evs.get(stat) > MAX_EVS_PER_STAT {
+            report(set.lines.evs, format!("{} {} EVs is more than {MAX_EVS_PER_STAT}", set.evs.get(stat), stat.label()));
+        }
+        if set.ivs.get(stat) > MAX_IV {
+            report(set.lines.ivs, format!("{} {} IVs is more than {MAX_IV}", set.ivs.get(stat), stat.label()));
+        }
+    }
+    if set.evs.total() > MAX_EVS_TOTAL {
+        report(set.lines.evs, format!("{} total EVs is more than {MAX_EVS_TOTAL}", set.evs.total()));
+    }
+
+    if set.moves.is_empty() {
+        report(None, "Set has no moves".to_string());
+    }
+    if set.moves.len() > MAX_MOVES {
+        report(set.lines.moves.get(MAX_MOVES).copied(), format!("Set has {} moves, the maximum is {MAX_MOVES}", set.moves.len()));
+    }
+    for (i, pokemon_move) in set.moves.iter().enumerate() {
+        let line_number = set.lines.moves.get(i).copied();
+        if dex.get_move(pokemon_move).is_none() {
+            report(line_number, format!("Unknown move '{pokemon_move}'"));
+        }
+        if set.moves[..i].contains(pokemon_move) {
+            report(line_number, format!("Duplicate move '{pokemon_move}'"));
+        }
+    }
+    diagnostics.sort_by_key(|diagnostic| diagnostic.line_number);
+    diagnostics
+}
+
+/// Validates every draft set folder under `dir_name`, printing diagnostics as `file:line: species: problem`.
+/// Returns the number of problems found.
+pub fn validate_data_folder(dir_name: &str) -> io::Result<usize> {
+    let dex = Dex::from_folder(&Path::new(dir_name).join(DEX_FOLDER))?;
+    let mut total_problems = 0;
+
+    let set_folders = find_set_folders(dir_name)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    for set_path in set_folders {
+        let set_name = set_path.file_name().unwrap_or_default().to_string_lossy();
+        let sets_file = set_path.join(DRAFT_SETS_FILE);
+        let sets_text = fs::read_to_string(&sets_file)?;
+        let pokemon_sets = match PokemonSet::parse_all(&sets_text) {
+            Ok(sets) => sets,
+            Err(e) => {
+                println!("{}:{}: {}", sets_file.display(), e.line_number, e.message);
+                total_problems += 1;
+                continue;
+            }
+        };
+
+        let mut set_problems = 0;
+        for set in &pokemon_sets {
+            for diagnostic in validate_set(set, &dex) {
+                println!("{}:{}: {}: {}", sets_file.display(), diagnostic.line_number, set.species, diagnostic.message);
+                set_problems += 1;
+            }
+        }
+        println!("{set_name}: checked {} sets, found {set_problems} problems", pokemon_sets.len());
+        total_problems += set_problems;
+    }
+    Ok(total_problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    use crate::test_fixtures::{load_dex, temp_data_folder, write_set};
+
+    const VALID_SET: &str = "\
+Garchomp @ Choice Band
+Ability: Rough Skin
+EVs: 252 Atk / 4 SpD / 252 Spe
+Jolly Nature
+- Earthquake
+- Outrage
+- Stone Edge
+- Fire Fang
+";
+
+    /// The problems found in `VALID_SET` with one line replaced
+    fn problems(from: &str, to: &str) -> Vec<String> {
+        let dex = load_dex();
+        let set = PokemonSet::parse_all(&VALID_SET.replace(from, to)).unwrap().remove(0);
+        validate_set(&set, &dex).into_iter().map(|diagnostic| diagnostic.message).collect()
+    }
+
+    #[test]
+    fn accepts_a_valid_set() {
+        assert!(problems("", "").is_empty());
+    }
+
+    #[test]
+    fn reports_unknown_names() {
+        assert_eq!(problems("Garchomp @", "Garchompy @"), vec!["Unknown species 'Garchompy'"]);
+        assert_eq!(problems("Rough Skin", "Levitate"), vec!["Garchomp can't have the ability 'Levitate'"]);
+        assert_eq!(problems("Choice Band", "Choice Bandana"), vec!["Unknown item 'Choice Bandana'"]);
+        assert_eq!(problems("Jolly", "Jolley"), vec!["Unknown nature 'Jolley'"]);
+        assert_eq!(problems("- Outrage", "- Outrageous"), vec!["Unknown move 'Outrageous'"]);
+    }
+
+    #[test]
+    fn reports_duplicate_and_extra_moves() {
+        assert_eq!(problems("- Outrage", "- Earthquake"), vec!["Duplicate move 'Earthquake'"]);
+        assert_eq!(problems("- Fire Fang\n", "- Fire Fang\n- Swords Dance\n"), vec!["Set has 5 moves, the maximum is 4"]);
+    }
+
+    #[test]
+    fn reports_evs_over_the_limits() {
+        assert_eq!(problems("252 Atk", "256 Atk"), vec!["256 Atk EVs is more than 252", "512 total EVs is more than 510"]);
+        assert_eq!(problems("4 SpD", "252 SpD"), vec!["756 total EVs is more than 510"]);
+        // A total too big for a u16 is still reported
+        let evs = "EVs: 65535 HP / 65535 Atk / 65535 Def / 65535 SpA / 65535 SpD / 65535 Spe";
+        assert!(problems("EVs: 252 Atk / 4 SpD / 252 Spe", evs).contains(&"393210 total EVs is more than 510".to_string()));
+    }
+
+    #[test]
+    fn reports_bad_ivs_levels_and_tera_types() {
+        assert_eq!(problems("Jolly Nature\n", "Jolly Nature\nIVs: 32 Spe\n"), vec!["32 Spe IVs is more than 31"]);
+        assert_eq!(problems("Ability: Rough Skin\n", "Ability: Rough Skin\nLevel: 0\n"), vec!["Level 0 is out of range"]);
+        assert_eq!(problems("Ability: Rough Skin\n", "Ability: Rough Skin\nLevel: 101\n"), vec!["Level 101 is out of range"]);
+        assert_eq!(problems("Ability: Rough Skin\n", "Ability: Rough Skin\nTera Type: Fire\n"),
+                   vec!["Tera Types aren't used in drafts, remove this line"]);
+    }
+
+    #[test]
+    fn validates_set_folders_whose_names_arent_utf8() {
+        let data_dir = temp_data_folder("validate");
+        write_set(&data_dir.join(OsStr::from_bytes(b"bad-\xff")), "Garchomp\n- Earthquake\n- Earthquake\n", None);
+        assert_eq!(validate_data_folder(data_dir.to_str().unwrap()).unwrap(), 1);
+    }
+}